@@ -82,9 +82,245 @@
 //!
 //! ```
 //!
+//! The derived `ColumnPositions` implementation also provides `column_names()`, returning
+//! the names of the columns read by the conversion, in the order of the fields.
+//! Use it to keep the list of selected columns in sync with the struct definition.
+//! The names set with `#[stargate(name)]` or `#[stargate(rename)]` are used instead of
+//! the field names, and the columns of a `#[stargate(flatten)]` field are inlined.
+//!
+//! ```
+//! use stargate_grpc::result::ColumnPositions;
+//! use stargate_grpc_derive::TryFromRow;
+//!
+//! #[derive(TryFromRow)]
+//! struct User {
+//!     id: i64,
+//!     #[stargate(name = "user_login")]
+//!     login: String,
+//! }
+//!
+//! let cql = format!("SELECT {} FROM users", User::column_names().join(", "));
+//! assert_eq!(cql, "SELECT id, user_login FROM users");
+//! ```
+//!
+//! ## Newtypes
+//! `IntoValue` and `TryFromValue` can be also derived for tuple structs with a single field.
+//! Such a struct is a transparent wrapper: it converts to and from the same CQL type
+//! as its inner field.
+//!
+//! ```
+//! use stargate_grpc::Value;
+//! use stargate_grpc_derive::{IntoValue, TryFromValue};
+//!
+//! #[derive(IntoValue, TryFromValue)]
+//! struct UserId(i64);
+//!
+//! assert_eq!(Value::from(UserId(10)), Value::bigint(10));
+//! let id: UserId = Value::bigint(10).try_into().unwrap();
+//! assert_eq!(id.0, 10);
+//! ```
+//!
+//! Set `#[stargate(cql_type = "type")]` on the inner field to get a typed
+//! `IntoValue` implementation. It is required to convert an `Option` of the newtype
+//! into a `Value`:
+//!
+//! ```
+//! use stargate_grpc::{types, Value};
+//! use stargate_grpc_derive::IntoValue;
+//!
+//! #[derive(IntoValue)]
+//! struct UserId(#[stargate(cql_type = "types::Bigint")] i64);
+//!
+//! assert_eq!(Value::from(Some(UserId(10))), Value::bigint(10));
+//! ```
+//!
 //! ## Options
 //! All macros defined in this module accept a `#[stargate]` attribute that you can set
 //! on struct fields to control the details of how the conversion should be made.
+//! An option set on the struct that the derived trait doesn't support is reported
+//! as a compile error.
+//!
+//! ### `#[stargate(as_map)]`
+//! Set on the struct, makes `IntoValue` and `TryFromValue` convert the struct to and from
+//! a CQL `map` with text keys, instead of a value of a user defined type.
+//! Use it if the data is stored in a `map<text, ...>` column.
+//! Each field becomes an entry of the map, keyed by the field name.
+//!
+//! Unlike a UDT, a map has no schema, so the keys are not checked on the server side.
+//! All the values in a CQL map have the same type, so the field types must be
+//! compatible with the value type of the column.
+//!
+//! ```
+//! use stargate_grpc::Value;
+//! use stargate_grpc_derive::{IntoValue, TryFromValue};
+//!
+//! #[derive(IntoValue, TryFromValue)]
+//! #[stargate(as_map)]
+//! struct Labels {
+//!     color: String,
+//!     size: String,
+//! }
+//!
+//! let labels = Labels { color: "red".to_string(), size: "XL".to_string() };
+//! let value = Value::from(labels);
+//! assert_eq!(value, Value::map(vec![("color", "red"), ("size", "XL")]));
+//! ```
+//!
+//! ### `#[stargate(as_text)]`
+//! Set on a struct or an enum, makes `IntoValue` and `TryFromValue` convert it to and from
+//! a CQL `text` value, using its `Display` and `FromStr` implementations.
+//! Fields are not looked at, so this works also for enums,
+//! e.g. the ones deriving `strum::Display` and `strum::EnumString`.
+//! A string that cannot be parsed fails the conversion with `ConversionErrorKind::Incompatible`.
+//!
+//! ```
+//! use std::fmt::{Display, Formatter};
+//! use std::str::FromStr;
+//! use stargate_grpc::Value;
+//! use stargate_grpc_derive::{IntoValue, TryFromValue};
+//!
+//! #[derive(Debug, PartialEq, IntoValue, TryFromValue)]
+//! #[stargate(as_text)]
+//! enum Color {
+//!     Red,
+//!     Green,
+//! }
+//!
+//! impl Display for Color {
+//!     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+//!         write!(f, "{:?}", self)
+//!     }
+//! }
+//!
+//! impl FromStr for Color {
+//!     type Err = ();
+//!     fn from_str(s: &str) -> Result<Self, Self::Err> {
+//!         match s {
+//!             "Red" => Ok(Color::Red),
+//!             "Green" => Ok(Color::Green),
+//!             _ => Err(()),
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Value::from(Color::Red), Value::string("Red"));
+//! let color: Color = Value::string("Green").try_into().unwrap();
+//! assert_eq!(color, Color::Green);
+//! ```
+//!
+//! ### `#[stargate(as_bits)]`
+//! Set on a struct, makes `IntoValue` and `TryFromValue` convert it to and from an integer
+//! value, using its `bits()` and `from_bits(bits) -> Option<Self>` methods.
+//! This matches the API of flag types generated by the `bitflags` crate, so you can
+//! add `#[derive(IntoValue, TryFromValue)]` and `#[stargate(as_bits)]` inside the `bitflags!`
+//! macro invocation. The value is a `bigint`, so `bits()` must return a type convertible
+//! to `bigint`, i.e. a signed integer or an unsigned integer of up to 32 bits.
+//! Like other derived types, such a struct can be used in collections, options
+//! and fields of other structs.
+//! Reading an integer with bits not defined by any flag fails with
+//! `ConversionErrorKind::OutOfRange`.
+//!
+//! ```
+//! use stargate_grpc::Value;
+//! use stargate_grpc_derive::{IntoValue, TryFromValue};
+//!
+//! #[derive(Debug, PartialEq, IntoValue, TryFromValue)]
+//! #[stargate(as_bits)]
+//! struct Permissions(u32);
+//!
+//! impl Permissions {
+//!     fn bits(&self) -> u32 {
+//!         self.0
+//!     }
+//!     fn from_bits(bits: u32) -> Option<Self> {
+//!         if bits & !0b11 == 0 { Some(Permissions(bits)) } else { None }
+//!     }
+//! }
+//!
+//! assert_eq!(Value::from(Permissions(0b10)), Value::bigint(2));
+//! let permissions: Permissions = Value::bigint(3).try_into().unwrap();
+//! assert_eq!(permissions, Permissions(0b11));
+//! assert!(Value::bigint(4).try_into::<Permissions>().is_err());
+//! ```
+//!
+//! ### `#[stargate(flatten_map)]`
+//! Set on a field of type `HashMap<String, Value>`, makes it a catch-all for the fields
+//! of the UDT (or the entries of the map, with `#[stargate(as_map)]`) that are not mapped to
+//! any other field of the struct. `TryFromValue` puts the unmatched fields there,
+//! and `IntoValue` writes them back next to the mapped fields. If a field is present in both,
+//! the mapped field wins.
+//!
+//! This allows reading and writing back UDTs that got new fields added in the schema,
+//! without losing data. There can be only one such field in a struct.
+//! The attribute is supported only by `IntoValue` and `TryFromValue`;
+//! `IntoValues` and `TryFromRow` reject it.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use stargate_grpc::Value;
+//! use stargate_grpc_derive::{IntoValue, TryFromValue};
+//!
+//! #[derive(IntoValue, TryFromValue)]
+//! struct User {
+//!     id: i64,
+//!     #[stargate(flatten_map)]
+//!     other: HashMap<String, Value>,
+//! }
+//!
+//! let value = Value::udt(vec![("id", Value::bigint(1)), ("login", Value::string("user"))]);
+//! let user: User = value.clone().try_into().unwrap();
+//! assert_eq!(user.id, 1);
+//! assert_eq!(user.other["login"], Value::string("user"));
+//! assert_eq!(Value::from(user), value);
+//! ```
+//!
+//! ### `#[stargate(flatten)]`
+//! Set on a field of a struct deriving `TryFromRow`, reads the field from several columns
+//! of the row, using the `TryFromRow` implementation of the field type.
+//! This allows composing row types from smaller structs.
+//!
+//! The fields of the nested struct are matched to the columns of the same result set
+//! by their own names, or the names given with `#[stargate(name)]` in the nested struct.
+//! The names are not prefixed with the name of the flattened field, so they must not collide
+//! with the column names used by the outer struct. Each column can be read only once;
+//! if two fields map to the same column, creating the mapper fails with
+//! `MapperError::DuplicateColumn`. `#[stargate(case_insensitive)]` applies only to the struct
+//! it is set on.
+//!
+//! The type of a flattened field may also implement `ColumnPositions` and `TryFromRow`
+//! by hand. Its `column_names()` must then return one name per column position,
+//! because the outer struct uses it to tell which positions belong to the nested value.
+//! Otherwise, creating the mapper fails with `MapperError::ColumnNamesMismatch`.
+//!
+//! ```
+//! use stargate_grpc::*;
+//! use stargate_grpc::proto::ColumnSpec;
+//! use stargate_grpc_derive::TryFromRow;
+//!
+//! #[derive(TryFromRow)]
+//! struct Address {
+//!     street: String,
+//!     city: String,
+//! }
+//!
+//! #[derive(TryFromRow)]
+//! struct User {
+//!     id: i64,
+//!     #[stargate(flatten)]
+//!     address: Address,
+//! }
+//!
+//! let column = |name: &str| ColumnSpec { r#type: None, name: name.to_string() };
+//! let result_set = ResultSet {
+//!     columns: vec![column("city"), column("id"), column("street")],
+//!     rows: vec![Row { values: vec![Value::string("Paris"), Value::bigint(1), Value::string("Main")] }],
+//!     paging_state: None,
+//! };
+//! let mapper = result_set.mapper().unwrap();
+//! let user: User = mapper.try_unpack(result_set.rows[0].clone()).unwrap();
+//! assert_eq!(user.id, 1);
+//! assert_eq!(user.address.city, "Paris");
+//! ```
 //!
 //! ### `#[stargate(skip)]`
 //! Skips the field when doing the conversion to `Value`. This is useful when the structure
@@ -97,6 +333,38 @@
 //! if the source `Value` doesn't contain the field, or if the field is set to `Value::null`
 //! or `Value::unset`.
 //!
+//! ### `#[stargate(case_insensitive)]`
+//! Set on the struct, makes `TryFromRow` match the fields to the result set columns
+//! ignoring the case of the names. A column with exactly the same name as the field is
+//! preferred. If no column has exactly the same name, but several columns differ from
+//! the field name only by case, the mapper cannot be created and
+//! `MapperError::AmbiguousColumn` is returned.
+//! Not supported by the other derives.
+//!
+//! ### `#[stargate(default_all)]`
+//! Set on the struct, applies `#[stargate(default)]` to every field.
+//! Useful for reading UDTs that evolve over time, where new fields may be missing in
+//! older data. A `#[stargate(default = "expression")]` set on a field takes precedence.
+//! Supported by `TryFromValue`, and accepted by `IntoValue` so both can be derived
+//! for the same struct.
+//!
+//! ```
+//! use stargate_grpc::Value;
+//! use stargate_grpc_derive::TryFromValue;
+//!
+//! #[derive(TryFromValue)]
+//! #[stargate(default_all)]
+//! struct Settings {
+//!     theme: String,
+//!     #[stargate(default = "20")]
+//!     page_size: i64,
+//! }
+//!
+//! let settings: Settings = Value::udt(Vec::<(&str, Value)>::new()).try_into().unwrap();
+//! assert_eq!(settings.theme, "");
+//! assert_eq!(settings.page_size, 20);
+//! ```
+//!
 //! ### `#[stargate(default = "expression")]`
 //! Obtains the default value by evaluating given Rust expression given as a string.
 //!
@@ -117,6 +385,8 @@
 //! ### `#[stargate(cql_type = "type")]`
 //! Sets the target CQL type the field should be converted into, useful
 //! when there are multiple possibilities.
+//! The type must be composed of the structs defined in `stargate_grpc::types`.
+//! Unknown type names are reported as compile errors.
 //!
 //! ```
 //! use stargate_grpc::types;
@@ -135,9 +405,16 @@
 //! Sets the CQL field, column or query argument name associated with the field.
 //! If not given, it is assumed to be the same as struct field name.
 //!
+//! ### `#[stargate(rename = "column")]`
+//! Alias for `#[stargate(name = "column")]`, familiar to `serde` users.
+//! Setting both `name` and `rename` on the same field is an error.
+//!
+//! Any other key in the `#[stargate]` attribute is rejected with a compile error
+//! pointing at the unrecognized key.
+//!
 use proc_macro::TokenStream;
 
-use darling::util::Override;
+use darling::util::{Override, SpannedValue};
 use darling::{ast, util, FromDeriveInput, FromField};
 use quote::quote;
 use syn::__private::TokenStream2;
@@ -154,13 +431,206 @@ struct UdtField {
     #[darling(default)]
     skip: bool,
     #[darling(default)]
+    flatten_map: bool,
+    #[darling(default)]
+    flatten: bool,
+    #[darling(default)]
     name: Option<String>,
+    #[darling(default)]
+    rename: Option<String>,
 }
 
 #[derive(Debug, FromDeriveInput)]
+#[darling(attributes(stargate))]
 struct Udt {
     ident: syn::Ident,
     data: ast::Data<util::Ignored, UdtField>,
+    #[darling(default)]
+    as_map: SpannedValue<bool>,
+    #[darling(default)]
+    as_text: SpannedValue<bool>,
+    #[darling(default)]
+    as_bits: SpannedValue<bool>,
+    #[darling(default)]
+    default_all: SpannedValue<bool>,
+    #[darling(default)]
+    case_insensitive: SpannedValue<bool>,
+}
+
+/// Parses the input of a derive macro together with the `#[stargate]` attributes.
+///
+/// Resolves the `rename` attribute into `name` and applies `default_all` to the fields,
+/// so the code generators need to look only at the field attributes.
+fn parse_udt(tokens: TokenStream) -> Result<Udt, darling::Error> {
+    let parsed: syn::DeriveInput = syn::parse(tokens)?;
+    let mut udt = Udt::from_derive_input(&parsed)?;
+    let default_all = *udt.default_all;
+    if let ast::Data::Struct(s) = &mut udt.data {
+        let mut errors = Vec::new();
+        for field in s.fields.iter_mut() {
+            if default_all && field.default.is_none() {
+                field.default = Some(Override::Inherit);
+            }
+            if let Some(cql_type) = &field.cql_type {
+                if let Err(message) = check_cql_type(cql_type) {
+                    errors.push(darling::Error::custom(message).with_span(&field.ty));
+                }
+            }
+            match (&field.name, field.rename.take()) {
+                (Some(_), Some(_)) => errors.push(
+                    darling::Error::custom("`name` and `rename` cannot be used together")
+                        .with_span(&field.ty),
+                ),
+                (None, Some(rename)) => field.name = Some(rename),
+                _ => {}
+            }
+        }
+        if let Some(field) = s.fields.iter().filter(|f| f.flatten_map).nth(1) {
+            errors.push(
+                darling::Error::custom("Only one field can be marked with `flatten_map`")
+                    .with_span(&field.ty),
+            );
+        }
+        if s.style == ast::Style::Tuple && s.fields.len() != 1 {
+            errors.push(
+                darling::Error::custom("Tuple structs must have exactly one field")
+                    .with_span(&udt.ident),
+            );
+        }
+        if !errors.is_empty() {
+            return Err(darling::Error::multiple(errors));
+        }
+    }
+    Ok(udt)
+}
+
+/// Reports an error for each field marked with `flatten_map` in a derive that doesn't support it.
+fn reject_flatten_map(udt: Udt, derive: &str) -> Result<Udt, darling::Error> {
+    if let ast::Data::Struct(s) = &udt.data {
+        let errors: Vec<_> = s
+            .fields
+            .iter()
+            .filter(|f| f.flatten_map)
+            .map(|f| {
+                darling::Error::custom(format!("`flatten_map` is not supported by `{}`", derive))
+                    .with_span(&f.ty)
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(darling::Error::multiple(errors));
+        }
+    }
+    Ok(udt)
+}
+
+/// Reports an error for each of the given struct attributes that is set
+/// in a derive that doesn't support it.
+fn reject_struct_attributes(udt: Udt, derive: &str, names: &[&str]) -> Result<Udt, darling::Error> {
+    let attributes = [
+        ("as_map", &udt.as_map),
+        ("as_text", &udt.as_text),
+        ("as_bits", &udt.as_bits),
+        ("default_all", &udt.default_all),
+        ("case_insensitive", &udt.case_insensitive),
+    ];
+    let errors: Vec<_> = attributes
+        .iter()
+        .filter(|(name, set)| ***set && names.contains(name))
+        .map(|(name, set)| {
+            darling::Error::custom(format!("`{}` is not supported by `{}`", name, derive))
+                .with_span(*set)
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(darling::Error::multiple(errors));
+    }
+    Ok(udt)
+}
+
+/// Reports an error for a tuple struct in a derive that supports only structs with named fields.
+fn reject_tuple_struct(udt: Udt, derive: &str) -> Result<Udt, darling::Error> {
+    if newtype_field(&udt).is_some() {
+        return Err(darling::Error::custom(format!(
+            "Tuple structs are not supported by `{}`",
+            derive
+        ))
+        .with_span(&udt.ident));
+    }
+    Ok(udt)
+}
+
+/// Names of the structs defined in `stargate_grpc::types`.
+const CQL_TYPES: &[&str] = &[
+    "Any",
+    "Bigint",
+    "Blob",
+    "Boolean",
+    "Counter",
+    "Date",
+    "Decimal",
+    "Double",
+    "Float",
+    "Inet",
+    "Int",
+    "List",
+    "Map",
+    "Set",
+    "Smallint",
+    "Text",
+    "Time",
+    "Timestamp",
+    "Timeuuid",
+    "Tinyint",
+    "Udt",
+    "Uuid",
+    "Varchar",
+    "Varint",
+];
+
+/// Checks that the `cql_type` attribute refers only to the types from `stargate_grpc::types`.
+///
+/// This is a best-effort check to report typos in a readable way.
+/// Every capitalized identifier is assumed to name a type, lowercase ones are module names.
+fn check_cql_type(cql_type: &str) -> Result<(), String> {
+    let tokens: TokenStream2 = cql_type
+        .parse()
+        .map_err(|_| format!("Invalid `cql_type`: `{}`", cql_type))?;
+    let mut unknown = Vec::new();
+    find_unknown_types(tokens, &mut unknown);
+    match unknown.first() {
+        None => Ok(()),
+        Some(name) => Err(format!(
+            "Unknown CQL type `{}` in `cql_type`; expected one of the types defined in \
+            `stargate_grpc::types`: {}",
+            name,
+            CQL_TYPES.join(", ")
+        )),
+    }
+}
+
+fn find_unknown_types(tokens: TokenStream2, unknown: &mut Vec<String>) {
+    for token in tokens {
+        match token {
+            proc_macro2::TokenTree::Group(group) => find_unknown_types(group.stream(), unknown),
+            proc_macro2::TokenTree::Ident(ident) => {
+                let name = ident.to_string();
+                let capitalized = name.starts_with(|c: char| c.is_ascii_uppercase());
+                if capitalized && !CQL_TYPES.contains(&name.as_str()) {
+                    unknown.push(name);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the only field of a newtype struct like `struct UserId(i64)`,
+/// or `None` if the struct has named fields.
+fn newtype_field(udt: &Udt) -> Option<&UdtField> {
+    match &udt.data {
+        ast::Data::Struct(s) if s.style == ast::Style::Tuple => s.fields.first(),
+        _ => None,
+    }
 }
 
 fn get_fields(udt: ast::Data<util::Ignored, UdtField>) -> Vec<UdtField> {
@@ -170,6 +640,12 @@ fn get_fields(udt: ast::Data<util::Ignored, UdtField>) -> Vec<UdtField> {
     }
 }
 
+/// Separates the `flatten_map` field from the fields mapped by name.
+fn split_flatten_map(fields: Vec<UdtField>) -> (Vec<UdtField>, Option<UdtField>) {
+    let (flatten_map, fields): (Vec<_>, Vec<_>) = fields.into_iter().partition(|f| f.flatten_map);
+    (fields, flatten_map.into_iter().next())
+}
+
 fn field_idents(fields: &[UdtField]) -> Vec<&syn::Ident> {
     fields.iter().map(|f| f.ident.as_ref().unwrap()).collect()
 }
@@ -212,8 +688,43 @@ fn convert_to_values(obj: &syn::Ident, fields: &[UdtField]) -> Vec<TokenStream2>
 /// Derives the `IntoValue` and `DefaultCqlType` implementations for a struct.
 #[proc_macro_derive(IntoValue, attributes(stargate))]
 pub fn derive_into_value(tokens: TokenStream) -> TokenStream {
-    let parsed = syn::parse(tokens).unwrap();
-    let udt: Udt = Udt::from_derive_input(&parsed).unwrap();
+    let udt = match parse_udt(tokens)
+        .and_then(|udt| reject_struct_attributes(udt, "IntoValue", &["case_insensitive"]))
+    {
+        Ok(udt) => udt,
+        Err(e) => return e.write_errors().into(),
+    };
+    if *udt.as_text {
+        let ident = udt.ident;
+        let result = quote! {
+            impl stargate_grpc::into_value::IntoValue<stargate_grpc::types::Text> for #ident {
+                fn into_value(self) -> stargate_grpc::Value {
+                    stargate_grpc::Value::raw_string(self)
+                }
+            }
+            impl stargate_grpc::into_value::DefaultCqlType for #ident {
+                type C = stargate_grpc::types::Text;
+            }
+        };
+        return result.into();
+    }
+    if *udt.as_bits {
+        let ident = udt.ident;
+        let result = quote! {
+            impl stargate_grpc::into_value::IntoValue<stargate_grpc::types::Bigint> for #ident {
+                fn into_value(self) -> stargate_grpc::Value {
+                    stargate_grpc::Value::bigint(self.bits())
+                }
+            }
+            impl stargate_grpc::into_value::DefaultCqlType for #ident {
+                type C = stargate_grpc::types::Bigint;
+            }
+        };
+        return result.into();
+    }
+    if let Some(field) = newtype_field(&udt) {
+        return derive_newtype_into_value(&udt.ident, field);
+    }
     let udt_type = udt.ident;
 
     let obj = syn::Ident::new("obj", proc_macro2::Span::mixed_site());
@@ -221,30 +732,118 @@ pub fn derive_into_value(tokens: TokenStream) -> TokenStream {
         .into_iter()
         .filter(|f| !f.skip)
         .collect();
+    let (fields, flatten_map) = split_flatten_map(fields);
     let remote_field_names = field_names(&fields);
     let field_values: Vec<_> = convert_to_values(&obj, &fields);
 
-    let result = quote! {
-        impl stargate_grpc::into_value::IntoValue<stargate_grpc::types::Udt> for #udt_type {
-            fn into_value(self) -> stargate_grpc::Value {
-                let #obj = self;
-                let mut fields = std::collections::HashMap::new();
-                #(fields.insert(#remote_field_names.to_string(), #field_values));*;
-                stargate_grpc::Value::raw_udt(fields)
+    // Unmatched fields go first, so they are overwritten by the mapped fields:
+    let unmatched_fields = flatten_map.map(|f| {
+        let ident = f.ident.unwrap();
+        quote! { #obj.#ident }
+    });
+    let result = if *udt.as_map {
+        let entries = match unmatched_fields {
+            Some(unmatched) => quote! {
+                {
+                    let mut entries: std::collections::HashMap<String, stargate_grpc::Value> =
+                        #unmatched.into_iter().collect();
+                    #(entries.insert(#remote_field_names.to_string(), #field_values));*;
+                    entries
+                }
+            },
+            None => quote! { vec![#((#remote_field_names, #field_values)),*] },
+        };
+        quote! {
+            impl stargate_grpc::into_value::IntoValue<
+                stargate_grpc::types::Map<stargate_grpc::types::Text, stargate_grpc::types::Any>
+            > for #udt_type {
+                fn into_value(self) -> stargate_grpc::Value {
+                    let #obj = self;
+                    stargate_grpc::Value::map(#entries)
+                }
+            }
+            impl stargate_grpc::into_value::DefaultCqlType for #udt_type {
+                type C = stargate_grpc::types::Map<
+                    stargate_grpc::types::Text,
+                    stargate_grpc::types::Any
+                >;
             }
         }
-        impl stargate_grpc::into_value::DefaultCqlType for #udt_type {
-            type C = stargate_grpc::types::Udt;
+    } else {
+        let new_fields = match unmatched_fields {
+            Some(unmatched) => quote! { #unmatched.into_iter().collect() },
+            None => quote! { std::collections::HashMap::new() },
+        };
+        quote! {
+            impl stargate_grpc::into_value::IntoValue<stargate_grpc::types::Udt> for #udt_type {
+                fn into_value(self) -> stargate_grpc::Value {
+                    let #obj = self;
+                    let mut fields: std::collections::HashMap<String, stargate_grpc::Value> =
+                        #new_fields;
+                    #(fields.insert(#remote_field_names.to_string(), #field_values));*;
+                    stargate_grpc::Value::raw_udt(fields)
+                }
+            }
+            impl stargate_grpc::into_value::DefaultCqlType for #udt_type {
+                type C = stargate_grpc::types::Udt;
+            }
         }
     };
     result.into()
 }
 
+/// Derives the conversion of a newtype struct to a `Value`.
+/// The struct is converted in the same way as its inner field.
+///
+/// Without an explicit `cql_type`, the CQL type of the inner field is known only to the compiler,
+/// so we can't emit a typed `IntoValue` impl without conflicting with the blanket
+/// `IntoValue<types::Any>` impl. A `From` impl gives the untyped conversions.
+fn derive_newtype_into_value(newtype: &syn::Ident, field: &UdtField) -> TokenStream {
+    let inner_type = &field.ty;
+    let result = match &field.cql_type {
+        Some(t) => {
+            let cql_type = token_stream(t.as_str());
+            quote! {
+                impl stargate_grpc::into_value::IntoValue<#cql_type> for #newtype {
+                    fn into_value(self) -> stargate_grpc::Value {
+                        <#inner_type as stargate_grpc::into_value::IntoValue<#cql_type>>::into_value(self.0)
+                    }
+                }
+                impl stargate_grpc::into_value::DefaultCqlType for #newtype {
+                    type C = #cql_type;
+                }
+            }
+        }
+        None => quote! {
+            impl std::convert::From<#newtype> for stargate_grpc::Value {
+                fn from(value: #newtype) -> Self {
+                    stargate_grpc::Value::from(value.0)
+                }
+            }
+        },
+    };
+    result.into()
+}
+
 /// Derives the `IntoValues` impl that allows to use struct in `QueryBuilder::bind`
 #[proc_macro_derive(IntoValues, attributes(stargate))]
 pub fn derive_into_values(tokens: TokenStream) -> TokenStream {
-    let parsed = syn::parse(tokens).unwrap();
-    let udt: Udt = Udt::from_derive_input(&parsed).unwrap();
+    let udt = match parse_udt(tokens)
+        .and_then(|udt| reject_tuple_struct(udt, "IntoValues"))
+        .and_then(|udt| reject_flatten_map(udt, "IntoValues"))
+        .and_then(|udt| {
+            let names = [
+                "as_map",
+                "as_text",
+                "as_bits",
+                "default_all",
+                "case_insensitive",
+            ];
+            reject_struct_attributes(udt, "IntoValues", &names)
+        }) {
+        Ok(udt) => udt,
+        Err(e) => return e.write_errors().into(),
+    };
     let udt_type = udt.ident;
 
     let obj = syn::Ident::new("obj", proc_macro2::Span::mixed_site());
@@ -304,15 +903,119 @@ fn convert_from_hashmap_value(hashmap: &syn::Ident, field: &UdtField) -> TokenSt
 /// Derives the `TryFromValue` implementation for a struct.
 #[proc_macro_derive(TryFromValue, attributes(stargate))]
 pub fn derive_try_from_value(tokens: TokenStream) -> TokenStream {
-    let parsed = syn::parse(tokens).unwrap();
-    let udt: Udt = Udt::from_derive_input(&parsed).unwrap();
+    let udt = match parse_udt(tokens)
+        .and_then(|udt| reject_struct_attributes(udt, "TryFromValue", &["case_insensitive"]))
+    {
+        Ok(udt) => udt,
+        Err(e) => return e.write_errors().into(),
+    };
+    if *udt.as_text {
+        let ident = udt.ident;
+        let result = quote! {
+            impl stargate_grpc::from_value::TryFromValue for #ident {
+                fn try_from(value: stargate_grpc::Value) ->
+                    Result<Self, stargate_grpc::error::ConversionError>
+                {
+                    let text: String = value.try_into()?;
+                    match text.parse() {
+                        Ok(v) => Ok(v),
+                        Err(_) => Err(
+                            stargate_grpc::error::ConversionError::incompatible::<_, Self>(text)
+                        ),
+                    }
+                }
+            }
+
+            impl std::convert::TryFrom<stargate_grpc::Value> for #ident {
+                type Error = stargate_grpc::error::ConversionError;
+                fn try_from(value: stargate_grpc::Value) ->
+                    Result<Self, stargate_grpc::error::ConversionError>
+                {
+                    <#ident as stargate_grpc::from_value::TryFromValue>::try_from(value)
+                }
+            }
+        };
+        return result.into();
+    }
+    if *udt.as_bits {
+        let ident = udt.ident;
+        let result = quote! {
+            impl stargate_grpc::from_value::TryFromValue for #ident {
+                fn try_from(value: stargate_grpc::Value) ->
+                    Result<Self, stargate_grpc::error::ConversionError>
+                {
+                    let bits = value.try_into()?;
+                    #ident::from_bits(bits).ok_or_else(||
+                        stargate_grpc::error::ConversionError::out_of_range::<_, Self>(bits)
+                    )
+                }
+            }
+
+            impl std::convert::TryFrom<stargate_grpc::Value> for #ident {
+                type Error = stargate_grpc::error::ConversionError;
+                fn try_from(value: stargate_grpc::Value) ->
+                    Result<Self, stargate_grpc::error::ConversionError>
+                {
+                    <#ident as stargate_grpc::from_value::TryFromValue>::try_from(value)
+                }
+            }
+        };
+        return result.into();
+    }
+    if newtype_field(&udt).is_some() {
+        let ident = udt.ident;
+        let result = quote! {
+            impl stargate_grpc::from_value::TryFromValue for #ident {
+                fn try_from(value: stargate_grpc::Value) ->
+                    Result<Self, stargate_grpc::error::ConversionError>
+                {
+                    Ok(#ident(value.try_into()?))
+                }
+            }
+
+            impl std::convert::TryFrom<stargate_grpc::Value> for #ident {
+                type Error = stargate_grpc::error::ConversionError;
+                fn try_from(value: stargate_grpc::Value) ->
+                    Result<Self, stargate_grpc::error::ConversionError>
+                {
+                    <#ident as stargate_grpc::from_value::TryFromValue>::try_from(value)
+                }
+            }
+        };
+        return result.into();
+    }
     let ident = udt.ident;
-    let fields = get_fields(udt.data);
+    let (fields, flatten_map) = split_flatten_map(get_fields(udt.data));
     let field_idents = field_idents(&fields);
     let udt_hashmap = syn::Ident::new("fields", proc_macro2::Span::mixed_site());
     let field_values = fields
         .iter()
         .map(|field| convert_from_hashmap_value(&udt_hashmap, field));
+    // Mapped fields are removed from the hashmap as they are read, so what remains
+    // after reading them is unmatched:
+    let unmatched_field = flatten_map.map(|f| {
+        let ident = f.ident.unwrap();
+        quote! { #ident: #udt_hashmap.into_iter().collect(), }
+    });
+
+    // Collects the fields into a hashmap, so they can be looked up by name:
+    let read_fields = if *udt.as_map {
+        quote! {
+            Some(value::Inner::Collection(Collection { elements })) if elements.len() % 2 == 0 => {
+                let mut #udt_hashmap = std::collections::HashMap::new();
+                let mut elements = elements.into_iter();
+                while let (Some(k), Some(v)) = (elements.next(), elements.next()) {
+                    let k: String = k.try_into()?;
+                    #udt_hashmap.insert(k, v);
+                }
+                #udt_hashmap
+            }
+        }
+    } else {
+        quote! {
+            Some(value::Inner::Udt(UdtValue { #udt_hashmap })) => #udt_hashmap,
+        }
+    };
 
     let result = quote! {
 
@@ -323,14 +1026,15 @@ pub fn derive_try_from_value(tokens: TokenStream) -> TokenStream {
                 use stargate_grpc::Value;
                 use stargate_grpc::error::ConversionError;
                 use stargate_grpc::proto::*;
-                match value.inner {
-                    Some(value::Inner::Udt(UdtValue { mut #udt_hashmap })) => {
-                        Ok(#ident {
-                            #(#field_idents: #field_values?),*
-                        })
-                    }
-                    other => Err(ConversionError::incompatible::<_, Self>(other))
-                }
+                let mut #udt_hashmap = match value.inner {
+                    #read_fields
+                    other => return Err(ConversionError::incompatible::<_, Self>(other))
+                };
+                #(let #field_idents = #field_values?;)*
+                Ok(#ident {
+                    #unmatched_field
+                    #(#field_idents),*
+                })
             }
         }
 
@@ -350,31 +1054,110 @@ pub fn derive_try_from_value(tokens: TokenStream) -> TokenStream {
 /// Derives the `TryFromRow` implementation for a struct.
 #[proc_macro_derive(TryFromRow, attributes(stargate))]
 pub fn derive_try_from_typed_row(tokens: TokenStream) -> TokenStream {
-    let parsed = syn::parse(tokens).unwrap();
-    let udt: Udt = Udt::from_derive_input(&parsed).unwrap();
+    let udt = match parse_udt(tokens)
+        .and_then(|udt| reject_tuple_struct(udt, "TryFromRow"))
+        .and_then(|udt| reject_flatten_map(udt, "TryFromRow"))
+        .and_then(|udt| {
+            let names = ["as_map", "as_text", "as_bits", "default_all"];
+            reject_struct_attributes(udt, "TryFromRow", &names)
+        }) {
+        Ok(udt) => udt,
+        Err(e) => return e.write_errors().into(),
+    };
     let ident = udt.ident;
+    let case_insensitive = *udt.case_insensitive;
     let fields = get_fields(udt.data);
     let field_idents = field_idents(&fields);
     let field_names = field_names(&fields);
-    let indexes = 0..field_idents.len();
+    let column_positions = syn::Ident::new("column_positions", proc_macro2::Span::mixed_site());
+    let find_column = |(field, name): (&UdtField, &String)| {
+        let field_type = &field.ty;
+        if field.flatten {
+            quote! {
+                result.extend(
+                    stargate_grpc::result::nested_column_positions::<#field_type>(
+                        #column_positions.clone()
+                    )?
+                )
+            }
+        } else if case_insensitive {
+            quote! {
+                result.push(
+                    stargate_grpc::result::column_position_ignore_case(&#column_positions, #name)?
+                )
+            }
+        } else {
+            quote! {
+                result.push(
+                    *#column_positions
+                        .get(#name)
+                        .ok_or_else(|| MapperError::ColumnNotFound(#name))?
+                )
+            }
+        }
+    };
+    let positions = fields.iter().zip(&field_names).map(find_column);
+    let field_values = fields.iter().map(|field| {
+        let field_type = &field.ty;
+        if field.flatten {
+            quote! {
+                <#field_type as stargate_grpc::result::TryFromRow>::try_unpack_nested(
+                    row,
+                    #column_positions
+                )?
+            }
+        } else {
+            quote! {
+                row.values[*#column_positions.next().expect("column position")]
+                    .take()
+                    .try_into()?
+            }
+        }
+    });
+
+    let column_names = if fields.iter().any(|f| f.flatten) {
+        let append_names = fields.iter().zip(&field_names).map(|(field, name)| {
+            let field_type = &field.ty;
+            if field.flatten {
+                quote! {
+                    names.extend_from_slice(
+                        <#field_type as stargate_grpc::result::ColumnPositions>::column_names()
+                    )
+                }
+            } else {
+                quote! { names.push(#name) }
+            }
+        });
+        quote! {
+            static NAMES: std::sync::OnceLock<Vec<&'static str>> = std::sync::OnceLock::new();
+            NAMES.get_or_init(|| {
+                let mut names = Vec::new();
+                #(#append_names;)*
+                names
+            })
+        }
+    } else {
+        quote! { &[#(#field_names),*] }
+    };
 
     let result = quote! {
         impl stargate_grpc::result::ColumnPositions for #ident {
             fn field_to_column_pos(
-                column_positions: std::collections::HashMap<String, usize>
+                #column_positions: std::collections::HashMap<String, usize>
             ) -> Result<Vec<usize>, stargate_grpc::result::MapperError>
             {
                 use stargate_grpc::result::MapperError;
                 let mut result = Vec::new();
                 #(
-                    result.push(
-                        *column_positions
-                            .get(#field_names)
-                            .ok_or_else(|| MapperError::ColumnNotFound(#field_names))?
-                    );
+                    #positions;
                 )*
+                stargate_grpc::result::ensure_distinct_positions::<Self>(&result)?;
                 Ok(result)
             }
+
+            fn column_names() -> &'static [&'static str] {
+                #column_names
+            }
         }
 
         impl stargate_grpc::result::TryFromRow for #ident {
@@ -382,9 +1165,20 @@ pub fn derive_try_from_typed_row(tokens: TokenStream) -> TokenStream {
                 mut row: stargate_grpc::Row,
                 column_positions: &[usize]
             ) -> Result<Self, stargate_grpc::error::ConversionError>
+            {
+                <Self as stargate_grpc::result::TryFromRow>::try_unpack_nested(
+                    &mut row,
+                    &mut column_positions.iter()
+                )
+            }
+
+            fn try_unpack_nested(
+                row: &mut stargate_grpc::Row,
+                #column_positions: &mut std::slice::Iter<'_, usize>
+            ) -> Result<Self, stargate_grpc::error::ConversionError>
             {
                 Ok(#ident {
-                    #(#field_idents: row.values[column_positions[#indexes]].take().try_into()?),*
+                    #(#field_idents: #field_values),*
                 })
             }
         }
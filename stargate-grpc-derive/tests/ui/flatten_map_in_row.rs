@@ -0,0 +1,20 @@
+use std::collections::HashMap;
+
+use stargate_grpc::Value;
+use stargate_grpc_derive::{IntoValues, TryFromRow};
+
+#[derive(TryFromRow)]
+struct User {
+    id: i64,
+    #[stargate(flatten_map)]
+    other: HashMap<String, Value>,
+}
+
+#[derive(IntoValues)]
+struct UserValues {
+    id: i64,
+    #[stargate(flatten_map)]
+    other: HashMap<String, Value>,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use stargate_grpc_derive::IntoValue;
+
+#[derive(IntoValue)]
+struct User {
+    id: i64,
+    #[stargate(name = "login", rename = "user_login")]
+    login: String,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use stargate_grpc_derive::IntoValue;
+
+#[derive(IntoValue)]
+struct Point(i64, i64);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use stargate_grpc_derive::{IntoValues, TryFromRow};
+
+#[derive(TryFromRow)]
+struct Id(i64);
+
+#[derive(IntoValues)]
+struct Login(String);
+
+fn main() {}
@@ -0,0 +1,15 @@
+use std::collections::HashMap;
+
+use stargate_grpc::Value;
+use stargate_grpc_derive::TryFromValue;
+
+#[derive(TryFromValue)]
+struct User {
+    id: i64,
+    #[stargate(flatten_map)]
+    other: HashMap<String, Value>,
+    #[stargate(flatten_map)]
+    more: HashMap<String, Value>,
+}
+
+fn main() {}
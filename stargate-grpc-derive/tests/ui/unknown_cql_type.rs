@@ -0,0 +1,11 @@
+use stargate_grpc_derive::IntoValue;
+
+#[derive(IntoValue)]
+struct User {
+    #[stargate(cql_type = "types::Itn")]
+    id: i32,
+    #[stargate(cql_type = "types::List(types::Text)")]
+    tags: Vec<String>,
+}
+
+fn main() {}
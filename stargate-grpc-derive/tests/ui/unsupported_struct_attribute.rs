@@ -0,0 +1,33 @@
+use stargate_grpc_derive::{IntoValue, IntoValues, TryFromRow, TryFromValue};
+
+#[derive(TryFromRow)]
+#[stargate(default_all, as_map)]
+struct User {
+    id: i64,
+}
+
+#[derive(IntoValues)]
+#[stargate(default_all)]
+struct UserValues {
+    id: i64,
+}
+
+#[derive(IntoValue, TryFromValue)]
+#[stargate(case_insensitive)]
+struct Address {
+    street: String,
+}
+
+#[derive(TryFromRow)]
+#[stargate(as_text)]
+struct Login {
+    name: String,
+}
+
+#[derive(TryFromRow)]
+#[stargate(as_bits)]
+struct Flags {
+    bits: i64,
+}
+
+fn main() {}
@@ -2,15 +2,17 @@
 
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
+use std::time::Duration;
 
 use tonic::codegen::http::uri::InvalidUri;
 use tonic::codegen::InterceptedService;
 use tonic::metadata::AsciiMetadataValue;
 use tonic::service::Interceptor;
-use tonic::transport::{ClientTlsConfig, Endpoint};
-use tonic::{Request, Status};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint, Uri};
+use tonic::{Code, Request, Status};
 
-use crate::proto::stargate_client;
+use crate::proto::{stargate_client, Batch, BatchParameters, BatchQuery, Query, Response};
+use crate::query::Statement;
 
 /// Error returned on an attempt to create an [`AuthToken`] from an invalid string.
 #[derive(Clone, Debug)]
@@ -27,6 +29,68 @@ impl Display for InvalidAuthToken {
 
 impl std::error::Error for InvalidAuthToken {}
 
+/// Error returned by [`AuthToken::from_env`].
+#[derive(Clone, Debug)]
+pub enum AuthTokenFromEnvError {
+    /// When the environment variable with the given name is not set
+    /// or does not contain valid unicode.
+    NotSet(String),
+    /// When the value of the environment variable is not a valid token.
+    Invalid(String, InvalidAuthToken),
+}
+
+impl Display for AuthTokenFromEnvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthTokenFromEnvError::NotSet(var) => {
+                write!(f, "Environment variable {} not set", var)
+            }
+            AuthTokenFromEnvError::Invalid(var, e) => {
+                write!(f, "Environment variable {}: {}", var, e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthTokenFromEnvError {}
+
+/// Error returned by [`StargateClientBuilder::from_env`] and
+/// [`StargateClient::connect_from_env`].
+#[derive(Debug)]
+pub enum ClientFromEnvError {
+    /// When a mandatory environment variable is not set.
+    NotSet(String),
+    /// When the value of the environment variable is not valid.
+    /// Holds the name of the variable and the description of the problem.
+    Invalid(String, String),
+    /// When TLS is enabled, but the default TLS configuration could not be loaded.
+    Tls(std::io::Error),
+    /// When the connection could not be established.
+    Connect(tonic::transport::Error),
+}
+
+impl Display for ClientFromEnvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientFromEnvError::NotSet(var) => write!(f, "Environment variable {} not set", var),
+            ClientFromEnvError::Invalid(var, e) => {
+                write!(f, "Environment variable {}: {}", var, e)
+            }
+            ClientFromEnvError::Tls(e) => write!(f, "Failed to load TLS configuration: {}", e),
+            ClientFromEnvError::Connect(e) => write!(f, "Failed to connect: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientFromEnvError {}
+
+/// Name of the environment variable holding the Stargate URI(s).
+pub const URI_ENV_VAR: &str = "STARGATE_URI";
+/// Name of the environment variable holding the authentication token.
+pub const TOKEN_ENV_VAR: &str = "SG_TOKEN";
+/// Name of the environment variable enabling TLS.
+pub const TLS_ENV_VAR: &str = "STARGATE_TLS";
+
 /// Stores a token for authenticating to Stargate.
 ///
 /// You can obtain the token by sending a POST request with a username and password
@@ -65,6 +129,23 @@ impl FromStr for AuthToken {
     }
 }
 
+impl AuthToken {
+    /// Reads the token from the environment variable with the given name.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use stargate_grpc::client::AuthToken;
+    ///
+    /// let token = AuthToken::from_env("SG_TOKEN").unwrap();
+    /// ```
+    pub fn from_env(var_name: &str) -> Result<AuthToken, AuthTokenFromEnvError> {
+        let value = std::env::var(var_name)
+            .map_err(|_| AuthTokenFromEnvError::NotSet(var_name.to_string()))?;
+        AuthToken::from_str(&value)
+            .map_err(|e| AuthTokenFromEnvError::Invalid(var_name.to_string(), e))
+    }
+}
+
 /// Allows to use `AuthToken` as a Tonic request interceptor that
 /// attaches its token value to request header "x-cassandra-token".
 impl Interceptor for AuthToken {
@@ -93,6 +174,181 @@ impl StargateClient {
     pub fn builder() -> StargateClientBuilder {
         Default::default()
     }
+
+    /// Configures the client from the environment variables and connects to Stargate.
+    ///
+    /// Both [`URI_ENV_VAR`] and [`TOKEN_ENV_VAR`] must be set.
+    /// See [`StargateClientBuilder::from_env`] for the details.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use stargate_grpc::StargateClient;
+    ///
+    /// # async fn connect() -> anyhow::Result<()> {
+    /// let client = StargateClient::connect_from_env().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_from_env() -> Result<StargateClient, ClientFromEnvError> {
+        let builder = StargateClientBuilder::from_env()?;
+        if builder.endpoints.is_empty() {
+            return Err(ClientFromEnvError::NotSet(URI_ENV_VAR.to_string()));
+        }
+        if builder.token.is_none() {
+            return Err(ClientFromEnvError::NotSet(TOKEN_ENV_VAR.to_string()));
+        }
+        builder.connect().await.map_err(ClientFromEnvError::Connect)
+    }
+
+    /// Executes a query without taking ownership of it.
+    ///
+    /// Allows to execute the same query many times, e.g. to retry it after a failure.
+    /// The query is copied once per call, to build the request.
+    pub async fn execute_query_ref(
+        &mut self,
+        query: &Query,
+    ) -> Result<tonic::Response<Response>, Status> {
+        self.execute_query(query.clone()).await
+    }
+
+    /// Executes the queries in a single batch.
+    ///
+    /// A convenient alternative to [`BatchBuilder`](crate::query::BatchBuilder)
+    /// for queries that have already been built, e.g. generated programmatically.
+    /// Only the CQL strings and bound values are taken from the queries.
+    /// All queries in the batch share the given `parameters`, e.g. the keyspace and
+    /// the consistency level; the parameters set on the individual queries are ignored.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use stargate_grpc::{Query, StargateClient};
+    /// use stargate_grpc::proto::BatchParameters;
+    ///
+    /// # async fn run(mut client: StargateClient) -> anyhow::Result<()> {
+    /// let queries = (0..10).map(|id| {
+    ///     Query::builder()
+    ///         .query("INSERT INTO ks.users (id) VALUES (?)")
+    ///         .bind((id,))
+    ///         .build()
+    /// });
+    /// client.execute_batch_of(queries, BatchParameters::default()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_batch_of(
+        &mut self,
+        queries: impl IntoIterator<Item = Query>,
+        parameters: BatchParameters,
+    ) -> Result<tonic::Response<Response>, Status> {
+        let queries = queries
+            .into_iter()
+            .map(|query| BatchQuery {
+                cql: query.cql,
+                values: query.values,
+            })
+            .collect();
+        let batch = Batch {
+            r#type: 0,
+            queries,
+            parameters: Some(parameters),
+        };
+        self.execute_batch(batch).await
+    }
+
+    /// Executes a statement, retrying it up to `max_retries` times if it is idempotent
+    /// and fails with a transient error.
+    ///
+    /// The `Unavailable`, `DeadlineExceeded` and `Aborted` status codes are considered
+    /// transient. Other errors are returned immediately.
+    /// A statement that is not [idempotent](crate::query::Statement) is never retried,
+    /// because the failed attempt might have been applied by the server,
+    /// and executing it again could apply the same write twice.
+    /// The retries are sent right after a failure, without any delay.
+    ///
+    /// Returns the result of the last attempt.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use stargate_grpc::{Query, StargateClient};
+    ///
+    /// # async fn run(mut client: StargateClient) -> anyhow::Result<()> {
+    /// let statement = Query::builder()
+    ///     .query("UPDATE ks.users SET login = 'admin' WHERE id = 1")
+    ///     .idempotent(true)
+    ///     .build_statement();
+    /// let response = client.execute_statement(&statement, 3).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_statement(
+        &mut self,
+        statement: &Statement,
+        max_retries: u32,
+    ) -> Result<tonic::Response<Response>, Status> {
+        let mut retries = 0;
+        loop {
+            match self.execute_query_ref(&statement.query).await {
+                Err(status)
+                    if statement.idempotent && retries < max_retries && is_transient(&status) =>
+                {
+                    retries += 1
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Executes a query that must complete before the given deadline,
+    /// e.g. the deadline of an incoming request being served.
+    ///
+    /// The time remaining until the deadline is sent to the server as the
+    /// `grpc-timeout` request header, so the server can give up on the query
+    /// once nobody waits for the result. The deadline is also enforced on the client side,
+    /// so the call returns when the deadline passes, even if the server doesn't respond.
+    ///
+    /// If the deadline has already passed, the query is not sent at all.
+    /// In both cases, a `Status` with `Code::DeadlineExceeded` is returned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use stargate_grpc::{Query, StargateClient};
+    ///
+    /// # async fn run(mut client: StargateClient) -> anyhow::Result<()> {
+    /// let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    /// let query = Query::builder().query("SELECT * FROM ks.users").build();
+    /// let response = client.execute_query_with_deadline(query, deadline).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_query_with_deadline(
+        &mut self,
+        query: Query,
+        deadline: tokio::time::Instant,
+    ) -> Result<tonic::Response<Response>, Status> {
+        let timeout = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if timeout == Duration::from_secs(0) {
+            return Err(Status::deadline_exceeded(
+                "Deadline passed before sending the query",
+            ));
+        }
+        let mut request = Request::new(query);
+        request.set_timeout(timeout);
+        match tokio::time::timeout_at(deadline, self.execute_query(request)).await {
+            Ok(result) => result,
+            Err(_) => Err(Status::deadline_exceeded(
+                "Deadline passed while waiting for response",
+            )),
+        }
+    }
+}
+
+/// Returns true if the request failed for a reason that may go away when it is sent again.
+fn is_transient(status: &Status) -> bool {
+    matches!(
+        status.code(),
+        Code::Unavailable | Code::DeadlineExceeded | Code::Aborted
+    )
 }
 
 /// Returns the default TLS config with root certificates imported from the OS.
@@ -112,7 +368,11 @@ pub fn default_tls_config() -> std::io::Result<ClientTlsConfig> {
 pub struct StargateClientBuilder {
     token: Option<AuthToken>,
     tls_config: Option<ClientTlsConfig>,
-    endpoint: Option<Endpoint>,
+    endpoints: Vec<Endpoint>,
+    connect_timeout: Option<Duration>,
+    concurrency_limit: Option<usize>,
+    rate_limit: Option<(u64, Duration)>,
+    channel: Option<Channel>,
 }
 
 impl StargateClientBuilder {
@@ -120,6 +380,73 @@ impl StargateClientBuilder {
         Default::default()
     }
 
+    /// Creates a builder configured from the environment variables.
+    ///
+    /// Reads the following variables:
+    /// - `STARGATE_URI` ([`URI_ENV_VAR`]) – the URI of the Stargate coordinator,
+    ///   or a comma-separated list of URIs passed to [`uris`](StargateClientBuilder::uris);
+    /// - `SG_TOKEN` ([`TOKEN_ENV_VAR`]) – the authentication token;
+    /// - `STARGATE_TLS` ([`TLS_ENV_VAR`]) – `true` or `1` enables TLS with
+    ///   the [default configuration](default_tls_config), `false` or `0` disables it.
+    ///   TLS is disabled if the variable is not set.
+    ///
+    /// Variables that are not set are skipped, so the returned builder may be only partially
+    /// configured. Further settings can be applied to the returned builder as usual.
+    ///
+    /// # Errors
+    /// Returns [`ClientFromEnvError::Invalid`] if any of the variables is set to an invalid
+    /// value, or [`ClientFromEnvError::Tls`] if TLS is enabled, but the TLS configuration
+    /// could not be loaded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use stargate_grpc::client::StargateClientBuilder;
+    ///
+    /// # async fn connect() -> anyhow::Result<()> {
+    /// let client = StargateClientBuilder::from_env()?
+    ///     .connect_timeout(Duration::from_secs(10))
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_env() -> Result<Self, ClientFromEnvError> {
+        let mut builder = StargateClientBuilder::new();
+        if let Some(value) = env_var(URI_ENV_VAR)? {
+            let uris = value
+                .split(',')
+                .map(|uri| Uri::from_str(uri.trim()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| ClientFromEnvError::Invalid(URI_ENV_VAR.to_string(), e.to_string()))?;
+            builder = builder.uris(uris);
+        }
+        match AuthToken::from_env(TOKEN_ENV_VAR) {
+            Ok(token) => builder = builder.auth_token(token),
+            Err(AuthTokenFromEnvError::NotSet(_)) => {}
+            Err(AuthTokenFromEnvError::Invalid(var, e)) => {
+                return Err(ClientFromEnvError::Invalid(var, e.to_string()))
+            }
+        }
+        if let Some(value) = env_var(TLS_ENV_VAR)? {
+            let enabled = match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => {
+                    return Err(ClientFromEnvError::Invalid(
+                        TLS_ENV_VAR.to_string(),
+                        format!("expected true or false, got {}", value),
+                    ))
+                }
+            };
+            if enabled {
+                let tls = default_tls_config().map_err(ClientFromEnvError::Tls)?;
+                builder = builder.tls(Some(tls));
+            }
+        }
+        Ok(builder)
+    }
+
     /// Sets the stargate authentication token for authenticating the requests. Mandatory.
     pub fn auth_token(mut self, token: AuthToken) -> Self {
         self.token = Some(token);
@@ -132,26 +459,427 @@ impl StargateClientBuilder {
         self
     }
 
-    /// Sets the URL to connect to. Mandatory.
+    /// Sets the URL to connect to. Mandatory, unless [`uris`](StargateClientBuilder::uris) is used.
     pub fn uri(mut self, s: impl ToString) -> Result<Self, InvalidUri> {
-        self.endpoint = Some(Endpoint::from_str(s.to_string().as_str())?);
+        self.endpoints = vec![Endpoint::from_str(s.to_string().as_str())?];
         Ok(self)
     }
 
+    /// Sets the URLs of many Stargate coordinators to spread the requests across.
+    ///
+    /// If more than one URL is given, the client balances the load across all of them.
+    /// Each request goes to the less loaded one of two coordinators picked at random
+    /// ("power of two choices"), so the requests are distributed roughly evenly,
+    /// but not in strict round-robin order.
+    ///
+    /// [`connect`](StargateClientBuilder::connect) tries each coordinator once and leaves out
+    /// the ones that cannot be reached, so the requests are spread only across the reachable
+    /// ones. It fails only if none of the coordinators can be reached.
+    /// Coordinators left out are not retried later; connect again to include them.
+    /// A coordinator that becomes unreachable after connecting stays in the list.
+    /// A request sent to it returns an error, and the connection is attempted again
+    /// before the next request sent to it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use stargate_grpc::client::{AuthToken, StargateClient};
+    /// use std::str::FromStr;
+    ///
+    /// # async fn connect() -> anyhow::Result<()> {
+    /// let client = StargateClient::builder()
+    ///     .uris(vec![
+    ///         "http://stargate-1:8090".parse()?,
+    ///         "http://stargate-2:8090".parse()?,
+    ///     ])
+    ///     .auth_token(AuthToken::from_str("token")?)
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn uris(mut self, uris: Vec<Uri>) -> Self {
+        self.endpoints = uris.into_iter().map(Endpoint::from).collect();
+        self
+    }
+
+    /// Uses a channel built elsewhere instead of connecting to the configured URIs.
+    ///
+    /// Useful when the channel needs custom middleware or a custom connector.
+    /// [`connect`](StargateClientBuilder::connect) wraps the channel with the authentication
+    /// token, but skips building the endpoints, so the URIs, TLS, timeout and limit
+    /// settings of this builder are ignored. Configure them on the channel instead.
+    ///
+    /// A client built from a channel cannot reconnect, so
+    /// [`connect_reconnecting`](StargateClientBuilder::connect_reconnecting) panics
+    /// if the channel is set.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use stargate_grpc::client::{AuthToken, StargateClient};
+    /// use std::str::FromStr;
+    /// use tonic::transport::Endpoint;
+    ///
+    /// # async fn connect() -> anyhow::Result<()> {
+    /// let channel = Endpoint::from_static("http://localhost:8090")
+    ///     .tcp_nodelay(true)
+    ///     .connect()
+    ///     .await?;
+    /// let client = StargateClient::builder()
+    ///     .channel(channel)
+    ///     .auth_token(AuthToken::from_str("token")?)
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn channel(mut self, channel: Channel) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Sets the maximum time to wait for the connection to be established.
+    ///
+    /// Bounds only the initial connection, not the requests sent afterwards.
+    /// By default there is no timeout, so [`connect`](StargateClientBuilder::connect) may hang
+    /// as long as the operating system keeps trying to reach the server.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Limits the number of requests in flight to `limit`.
+    ///
+    /// When the limit is reached, further requests are not rejected,
+    /// but wait until one of the pending requests completes.
+    /// Clones of the client share the same connection and therefore the same limit.
+    /// If many coordinators are set with [`uris`](StargateClientBuilder::uris),
+    /// the limit applies to each coordinator separately.
+    ///
+    /// By default, the number of concurrent requests is not limited.
+    pub fn concurrency_limit(mut self, limit: usize) -> Self {
+        self.concurrency_limit = Some(limit);
+        self
+    }
+
+    /// Limits the rate of requests to at most `num` requests per `duration`.
+    ///
+    /// When the limit is reached, further requests are not rejected, but wait until
+    /// the current `duration` period elapses.
+    /// Clones of the client share the same connection and therefore the same limit.
+    /// If many coordinators are set with [`uris`](StargateClientBuilder::uris),
+    /// the limit applies to each coordinator separately.
+    ///
+    /// By default, the rate of requests is not limited.
+    pub fn rate_limit(mut self, num: u64, duration: Duration) -> Self {
+        self.rate_limit = Some((num, duration));
+        self
+    }
+
     /// Tries to connect to Stargate.
     ///
+    /// If a [`channel`](StargateClientBuilder::channel) is set, returns a client
+    /// using that channel without connecting.
+    ///
     /// # Errors
     /// Returns tonic transport error if the connection cannot be established.
+    /// If many coordinators are set with [`uris`](StargateClientBuilder::uris),
+    /// returns the error of the last coordinator tried if none of them can be reached.
     ///
     /// # Panics
     /// Panics if some mandatory settings hasn't been set.
     pub async fn connect(self) -> Result<StargateClient, tonic::transport::Error> {
-        let token = self.token.expect("Stargate authentication token not set");
-        let mut endpoint = self.endpoint.expect("Stargate URI not set");
-        if let Some(tls) = self.tls_config {
-            endpoint = endpoint.tls_config(tls)?
+        let token = self
+            .token
+            .clone()
+            .expect("Stargate authentication token not set");
+        if let Some(channel) = self.channel {
+            return Ok(StargateClient::with_auth(channel, token));
         }
-        let channel = endpoint.connect().await?;
+        let mut endpoints = self.configured_endpoints()?;
+        let channel = if endpoints.len() == 1 {
+            endpoints.remove(0).connect().await?
+        } else {
+            // The balanced channel connects lazily and can't reuse these connections,
+            // so they serve only to leave out the unreachable coordinators:
+            let mut reachable = Vec::with_capacity(endpoints.len());
+            let mut last_error = None;
+            for endpoint in endpoints {
+                match endpoint.connect().await {
+                    Ok(_) => reachable.push(endpoint),
+                    Err(e) => last_error = Some(e),
+                }
+            }
+            if reachable.is_empty() {
+                return Err(last_error.expect("connection error"));
+            }
+            Channel::balance_list(reachable.into_iter())
+        };
         Ok(StargateClient::with_auth(channel, token))
     }
+
+    /// Connects to Stargate like [`connect`](StargateClientBuilder::connect), but returns
+    /// a client that reconnects after `unavailable_errors` consecutive requests
+    /// fail with the `Unavailable` status.
+    ///
+    /// See [`ReconnectingClient`] for details.
+    ///
+    /// # Panics
+    /// Panics if some mandatory settings hasn't been set, if `unavailable_errors` is 0
+    /// or if a pre-built [`channel`](StargateClientBuilder::channel) is set.
+    pub async fn connect_reconnecting(
+        self,
+        unavailable_errors: u32,
+    ) -> Result<ReconnectingClient, tonic::transport::Error> {
+        assert!(
+            unavailable_errors > 0,
+            "unavailable_errors must be positive"
+        );
+        assert!(
+            self.channel.is_none(),
+            "Cannot reconnect a client built from a pre-built channel"
+        );
+        let token = self
+            .token
+            .clone()
+            .expect("Stargate authentication token not set");
+        let endpoints = self.configured_endpoints()?;
+        let client = self.connect().await?;
+        Ok(ReconnectingClient {
+            client,
+            endpoints,
+            token,
+            unavailable_errors,
+            unavailable_count: 0,
+            reconnect_count: 0,
+        })
+    }
+
+    /// Returns the endpoints with the TLS, timeout and limit settings applied.
+    fn configured_endpoints(&self) -> Result<Vec<Endpoint>, tonic::transport::Error> {
+        assert!(!self.endpoints.is_empty(), "Stargate URI not set");
+        let mut endpoints = Vec::with_capacity(self.endpoints.len());
+        for endpoint in &self.endpoints {
+            let mut endpoint = endpoint.clone();
+            if let Some(tls) = &self.tls_config {
+                endpoint = endpoint.tls_config(tls.clone())?
+            }
+            if let Some(timeout) = self.connect_timeout {
+                endpoint = endpoint.connect_timeout(timeout)
+            }
+            if let Some(limit) = self.concurrency_limit {
+                endpoint = endpoint.concurrency_limit(limit)
+            }
+            if let Some((num, duration)) = self.rate_limit {
+                endpoint = endpoint.rate_limit(num, duration)
+            }
+            endpoints.push(endpoint);
+        }
+        Ok(endpoints)
+    }
+}
+
+/// Returns the value of the environment variable or `None` if it is not set.
+fn env_var(name: &str) -> Result<Option<String>, ClientFromEnvError> {
+    match std::env::var(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(std::env::VarError::NotUnicode(_)) => Err(ClientFromEnvError::Invalid(
+            name.to_string(),
+            "not a valid unicode string".to_string(),
+        )),
+    }
+}
+
+/// A client that replaces its connection after repeated `Unavailable` errors.
+///
+/// A long-lived channel may get stuck on a coordinator that is no longer reachable,
+/// e.g. when Stargate runs behind a load balancer whose DNS name starts pointing to
+/// new IP addresses. This client counts the requests that fail with the
+/// [`Unavailable`](tonic::Code::Unavailable) status code in a row.
+/// When the count reaches the configured limit, the client drops its channel and creates
+/// a new one from the original URIs and settings. The new channel resolves the host names
+/// again and connects lazily, on the next request. Any successful response or an error
+/// with a different status code resets the count.
+///
+/// The request that triggers reconnecting still returns its error; this client doesn't
+/// retry any requests on its own. If you retry failed requests, every retry that fails with
+/// `Unavailable` counts towards the limit, so the reconnection happens while you retry.
+///
+/// Obtain one with [`StargateClientBuilder::connect_reconnecting`].
+///
+/// # Example
+/// ```no_run
+/// use std::str::FromStr;
+/// use stargate_grpc::client::{AuthToken, StargateClient};
+/// use stargate_grpc::Query;
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let mut client = StargateClient::builder()
+///     .uri("http://stargate.example.com:8090")?
+///     .auth_token(AuthToken::from_str("token")?)
+///     .connect_reconnecting(3)
+///     .await?;
+///
+/// let query = Query::builder().query("SELECT * FROM ks.users").build();
+/// let response = client.execute_query(query).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReconnectingClient {
+    client: StargateClient,
+    endpoints: Vec<Endpoint>,
+    token: AuthToken,
+    unavailable_errors: u32,
+    unavailable_count: u32,
+    reconnect_count: u32,
+}
+
+impl ReconnectingClient {
+    /// Executes a query, see [`StargateClient::execute_query`].
+    pub async fn execute_query(
+        &mut self,
+        query: Query,
+    ) -> Result<tonic::Response<Response>, Status> {
+        let result = self.client.execute_query(query).await;
+        self.track(&result);
+        result
+    }
+
+    /// Executes a batch, see [`StargateClient::execute_batch`].
+    pub async fn execute_batch(
+        &mut self,
+        batch: Batch,
+    ) -> Result<tonic::Response<Response>, Status> {
+        let result = self.client.execute_batch(batch).await;
+        self.track(&result);
+        result
+    }
+
+    /// Returns how many times the connection has been replaced so far.
+    pub fn reconnect_count(&self) -> u32 {
+        self.reconnect_count
+    }
+
+    /// Counts consecutive `Unavailable` errors and reconnects when there are too many.
+    fn track<T>(&mut self, result: &Result<T, Status>) {
+        match result {
+            Err(status) if status.code() == Code::Unavailable => {
+                self.unavailable_count += 1;
+                if self.unavailable_count >= self.unavailable_errors {
+                    self.reconnect();
+                }
+            }
+            _ => self.unavailable_count = 0,
+        }
+    }
+
+    /// Replaces the channel with a new one that connects lazily, on the next request.
+    fn reconnect(&mut self) {
+        let channel = if self.endpoints.len() == 1 {
+            match self.endpoints[0].connect_lazy() {
+                Ok(channel) => channel,
+                Err(_) => return,
+            }
+        } else {
+            Channel::balance_list(self.endpoints.clone().into_iter())
+        };
+        self.client = StargateClient::with_auth(channel, self.token.clone());
+        self.unavailable_count = 0;
+        self.reconnect_count += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::time::{Duration, Instant};
+
+    use crate::client::{
+        AuthToken, AuthTokenFromEnvError, ClientFromEnvError, StargateClientBuilder, TLS_ENV_VAR,
+        TOKEN_ENV_VAR, URI_ENV_VAR,
+    };
+    use crate::StargateClient;
+
+    #[test]
+    fn configure_builder_from_env() {
+        std::env::set_var(URI_ENV_VAR, "http://127.0.0.1:8090, http://127.0.0.2:8090");
+        std::env::set_var(TOKEN_ENV_VAR, "token");
+        std::env::set_var(TLS_ENV_VAR, "false");
+        let builder = StargateClientBuilder::from_env().unwrap();
+        assert_eq!(builder.endpoints.len(), 2);
+        assert_eq!(builder.endpoints[1].uri(), "http://127.0.0.2:8090/");
+        assert_eq!(builder.token, Some(AuthToken::from_str("token").unwrap()));
+        assert!(builder.tls_config.is_none());
+
+        std::env::set_var(TLS_ENV_VAR, "maybe");
+        match StargateClientBuilder::from_env() {
+            Err(ClientFromEnvError::Invalid(var, _)) => assert_eq!(var, TLS_ENV_VAR),
+            other => panic!("Unexpected result {:?}", other.map(|_| ())),
+        }
+        std::env::remove_var(TLS_ENV_VAR);
+
+        std::env::remove_var(URI_ENV_VAR);
+        std::env::remove_var(TOKEN_ENV_VAR);
+        let builder = StargateClientBuilder::from_env().unwrap();
+        assert!(builder.endpoints.is_empty());
+        assert!(builder.token.is_none());
+    }
+
+    #[test]
+    fn read_token_from_env() {
+        std::env::set_var("STARGATE_GRPC_TEST_TOKEN", "token");
+        let token = AuthToken::from_env("STARGATE_GRPC_TEST_TOKEN").unwrap();
+        assert_eq!(token, AuthToken::from_str("token").unwrap());
+    }
+
+    #[test]
+    fn read_invalid_token_from_env() {
+        std::env::set_var("STARGATE_GRPC_TEST_INVALID_TOKEN", "line\nbreak");
+        match AuthToken::from_env("STARGATE_GRPC_TEST_INVALID_TOKEN") {
+            Err(AuthTokenFromEnvError::Invalid(var, _)) => {
+                assert_eq!(var, "STARGATE_GRPC_TEST_INVALID_TOKEN")
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_missing_token_from_env() {
+        match AuthToken::from_env("STARGATE_GRPC_TEST_MISSING_TOKEN") {
+            Err(AuthTokenFromEnvError::NotSet(var)) => {
+                assert_eq!(var, "STARGATE_GRPC_TEST_MISSING_TOKEN")
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn connect_times_out() {
+        // A listener that never accepts connections and has its backlog already filled.
+        // New connection attempts are silently dropped, like on a black-hole address.
+        let socket = tokio::net::TcpSocket::new_v4().unwrap();
+        socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let listener = socket.listen(0).unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut backlog = Vec::new();
+        for _ in 0..4 {
+            let socket = tokio::net::TcpSocket::new_v4().unwrap();
+            let connect = socket.connect(address);
+            if let Ok(Ok(stream)) = tokio::time::timeout(Duration::from_millis(100), connect).await
+            {
+                backlog.push(stream);
+            }
+        }
+
+        let builder = StargateClient::builder()
+            .uri(format!("http://{}", address))
+            .unwrap()
+            .auth_token(AuthToken::from_str("token").unwrap())
+            .connect_timeout(Duration::from_millis(200));
+
+        let start = Instant::now();
+        let result = builder.connect().await;
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
 }
@@ -0,0 +1,331 @@
+//! Comparing values regardless of the order of set and map elements.
+//!
+//! The derived `PartialEq` of [`Value`] compares the elements of collections in order.
+//! This is right for lists and tuples, but two sets or maps holding the same elements
+//! in a different order are logically equal. The server doesn't guarantee any particular
+//! order of elements of a set or a map, so assertions comparing such values can be brittle.
+//!
+//! The wire format doesn't distinguish between lists, sets, maps and tuples,
+//! therefore [`Value::eq_unordered`] needs to be told the type of the compared values.
+//! The type is described by the same structs from the [`types`](crate::types) module
+//! that are used by [`Value::of_type`].
+//!
+//! # Example
+//! ```
+//! use stargate_grpc::Value;
+//! use stargate_grpc::types::{Bigint, List, Map, Text};
+//!
+//! let map1 = Value::map(vec![(1, "foo"), (2, "bar")]);
+//! let map2 = Value::map(vec![(2, "bar"), (1, "foo")]);
+//! assert_ne!(map1, map2);
+//! assert!(map1.eq_unordered(&map2, Map(Bigint, Text)));
+//!
+//! let list1 = Value::list(vec![1, 2]);
+//! let list2 = Value::list(vec![2, 1]);
+//! assert!(!list1.eq_unordered(&list2, List(Bigint)));
+//! ```
+//!
+//! Scalar values of the same type can be also ordered with [`Value::partial_cmp_scalar`].
+
+use std::cmp::Ordering;
+
+use crate::proto::value::Inner;
+use crate::types;
+use crate::Value;
+
+/// Compares values of a Cassandra type, ignoring the order of elements of sets and maps.
+///
+/// Implemented by the type-describing structs from the [`types`](crate::types) module.
+/// Values that don't have the structure declared by the type, e.g. a null in place
+/// of a collection, are compared with `==`.
+pub trait UnorderedEq {
+    fn values_eq(a: &Value, b: &Value) -> bool;
+}
+
+impl Value {
+    /// Returns true if this value is equal to `other`, ignoring the order of elements of
+    /// sets and maps, also when they are nested in other collections.
+    ///
+    /// The `type_spec` argument describes the type of both values and determines which
+    /// collections are compared without regard to the order of their elements.
+    /// See the [module documentation](crate::compare) for an example.
+    pub fn eq_unordered<C: UnorderedEq>(&self, other: &Value, _type_spec: C) -> bool {
+        C::values_eq(self, other)
+    }
+
+    /// Compares this value with `other` if both hold a scalar of the same kind.
+    ///
+    /// Orders integers, floating point numbers, booleans, strings, blobs, dates and times
+    /// by their natural order. Strings are ordered by their UTF-8 bytes and blobs by
+    /// their unsigned bytes, like Cassandra does. Floating point numbers follow the IEEE rules,
+    /// so comparing with a NaN returns `None`.
+    ///
+    /// Returns `None` if the values hold different kinds of scalars, if either of them is null,
+    /// a collection or a UDT, or if it is a UUID, an inet address, a varint or a decimal.
+    /// Hence, this is not a total order and values of different types are never comparable.
+    /// Different CQL types encoded with the same gRPC variant, e.g. `int` and `bigint`,
+    /// are compared as the same kind.
+    ///
+    /// # Example
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use stargate_grpc::Value;
+    ///
+    /// assert_eq!(Value::int(1).partial_cmp_scalar(&Value::int(2)), Some(Ordering::Less));
+    /// assert_eq!(Value::int(1).partial_cmp_scalar(&Value::string("1")), None);
+    /// ```
+    pub fn partial_cmp_scalar(&self, other: &Value) -> Option<Ordering> {
+        match (self.inner.as_ref()?, other.inner.as_ref()?) {
+            (Inner::Int(x), Inner::Int(y)) => Some(x.cmp(y)),
+            (Inner::Float(x), Inner::Float(y)) => x.partial_cmp(y),
+            (Inner::Double(x), Inner::Double(y)) => x.partial_cmp(y),
+            (Inner::Boolean(x), Inner::Boolean(y)) => Some(x.cmp(y)),
+            (Inner::String(x), Inner::String(y)) => Some(x.cmp(y)),
+            (Inner::Bytes(x), Inner::Bytes(y)) => Some(x.cmp(y)),
+            (Inner::Date(x), Inner::Date(y)) => Some(x.cmp(y)),
+            (Inner::Time(x), Inner::Time(y)) => Some(x.cmp(y)),
+            _ => None,
+        }
+    }
+}
+
+/// Returns true if every element of `a` can be paired with a distinct equal element of `b`.
+fn multiset_eq<T>(a: &[T], b: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut matched = vec![false; b.len()];
+    a.iter().all(
+        |x| match (0..b.len()).find(|&i| !matched[i] && eq(x, &b[i])) {
+            Some(i) => {
+                matched[i] = true;
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+/// Generates `UnorderedEq` for types with no elements to reorder.
+macro_rules! gen_ordered_eq {
+    ($($T:ty),+) => {
+        $(
+            impl UnorderedEq for $T {
+                fn values_eq(a: &Value, b: &Value) -> bool {
+                    a == b
+                }
+            }
+        )+
+    }
+}
+
+// Fields of UDT values are stored in a hash map, which is compared without regard to order
+gen_ordered_eq!(
+    types::Any,
+    types::Bigint,
+    types::Boolean,
+    types::Blob,
+    types::Counter,
+    types::Date,
+    types::Decimal,
+    types::Double,
+    types::Float,
+    types::Inet,
+    types::Int,
+    types::Smallint,
+    types::Text,
+    types::Time,
+    types::Timestamp,
+    types::Timeuuid,
+    types::Tinyint,
+    types::Udt,
+    types::Uuid,
+    types::Varchar,
+    types::Varint
+);
+
+impl<T: UnorderedEq> UnorderedEq for types::List<T> {
+    fn values_eq(a: &Value, b: &Value) -> bool {
+        match (a.as_collection(), b.as_collection()) {
+            (Some(x), Some(y)) => {
+                x.len() == y.len() && x.iter().zip(y).all(|(x, y)| T::values_eq(x, y))
+            }
+            _ => a == b,
+        }
+    }
+}
+
+impl<T: UnorderedEq> UnorderedEq for types::Set<T> {
+    fn values_eq(a: &Value, b: &Value) -> bool {
+        match (a.as_collection(), b.as_collection()) {
+            (Some(x), Some(y)) => multiset_eq(x, y, T::values_eq),
+            _ => a == b,
+        }
+    }
+}
+
+impl<K: UnorderedEq, V: UnorderedEq> UnorderedEq for types::Map<K, V> {
+    fn values_eq(a: &Value, b: &Value) -> bool {
+        match (a.as_collection(), b.as_collection()) {
+            (Some(x), Some(y)) if x.len() % 2 == 0 && y.len() % 2 == 0 => {
+                let x: Vec<_> = x.chunks_exact(2).collect();
+                let y: Vec<_> = y.chunks_exact(2).collect();
+                multiset_eq(&x, &y, |x, y| {
+                    K::values_eq(&x[0], &y[0]) && V::values_eq(&x[1], &y[1])
+                })
+            }
+            _ => a == b,
+        }
+    }
+}
+
+/// Generates `UnorderedEq` for tuples of the size denoted by the number of arguments.
+/// Tuple elements are compared in order.
+macro_rules! gen_tuple_eq {
+    ($($T:ident),+) => {
+        impl<$($T: UnorderedEq),+> UnorderedEq for ($($T),+,) {
+            fn values_eq(a: &Value, b: &Value) -> bool {
+                match (a.as_collection(), b.as_collection()) {
+                    (Some(x), Some(y)) => {
+                        let mut pairs = x.iter().zip(y);
+                        $(
+                            match pairs.next() {
+                                Some((x, y)) if $T::values_eq(x, y) => {}
+                                _ => return false,
+                            }
+                        )+
+                        x.len() == y.len() && pairs.next().is_none()
+                    }
+                    _ => a == b,
+                }
+            }
+        }
+    }
+}
+
+/// Calls `gen_tuple_eq!` recursively to generate comparisons for all tuples
+/// starting at size 1 and ending at the size specified by the number of arguments.
+macro_rules! gen_all_tuple_eqs {
+    ($first:ident) => {
+        gen_tuple_eq!($first);
+    };
+    ($first:ident, $($tail:ident),+) => {
+        gen_tuple_eq!($first, $($tail),+);
+        gen_all_tuple_eqs!($($tail),+);
+    }
+}
+
+// Generate comparisons for all tuples up to size 16
+gen_all_tuple_eqs!(A16, A15, A14, A13, A12, A11, A10, A9, A8, A7, A6, A5, A4, A3, A2, A1);
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use crate::types::{Any, Bigint, List, Map, Set, Text};
+    use crate::Value;
+
+    #[test]
+    fn reordered_maps_are_equal() {
+        let map1 = Value::map(vec![(1, "foo"), (2, "bar"), (3, "baz")]);
+        let map2 = Value::map(vec![(3, "baz"), (1, "foo"), (2, "bar")]);
+        assert!(map1.eq_unordered(&map2, Map(Bigint, Text)));
+    }
+
+    #[test]
+    fn different_maps_are_not_equal() {
+        let map = Value::map(vec![(1, "foo"), (2, "bar")]);
+        let swapped_values = Value::map(vec![(1, "bar"), (2, "foo")]);
+        let missing_entry = Value::map(vec![(1, "foo")]);
+        let duplicate_entry = Value::map(vec![(1, "foo"), (1, "foo")]);
+        assert!(!map.eq_unordered(&swapped_values, Map(Bigint, Text)));
+        assert!(!map.eq_unordered(&missing_entry, Map(Bigint, Text)));
+        assert!(!map.eq_unordered(&duplicate_entry, Map(Bigint, Text)));
+    }
+
+    #[test]
+    fn lists_are_compared_in_order() {
+        let list1 = Value::list(vec![1, 2, 3]);
+        let list2 = Value::list(vec![3, 2, 1]);
+        assert!(list1.eq_unordered(&list1, List(Bigint)));
+        assert!(!list1.eq_unordered(&list2, List(Bigint)));
+        assert!(list1.eq_unordered(&list2, Set(Bigint)));
+    }
+
+    #[test]
+    fn nested_sets_in_maps_are_compared_unordered() {
+        let map1 = Value::map(vec![
+            (1, Value::list(vec![1, 2])),
+            (2, Value::list(vec![3])),
+        ]);
+        let map2 = Value::map(vec![
+            (2, Value::list(vec![3])),
+            (1, Value::list(vec![2, 1])),
+        ]);
+        assert!(map1.eq_unordered(&map2, Map(Bigint, Set(Bigint))));
+        assert!(!map1.eq_unordered(&map2, Map(Bigint, List(Bigint))));
+        assert!(!map1.eq_unordered(&map2, Map(Bigint, Any)));
+    }
+
+    #[test]
+    fn tuples_are_compared_in_order() {
+        let tuple1 = Value::list(vec![Value::bigint(1), Value::list(vec![1, 2])]);
+        let tuple2 = Value::list(vec![Value::bigint(1), Value::list(vec![2, 1])]);
+        assert!(tuple1.eq_unordered(&tuple2, (Bigint, Set(Bigint))));
+        assert!(!tuple1.eq_unordered(&tuple2, (Bigint, List(Bigint))));
+        assert!(!tuple1.eq_unordered(&Value::list(vec![1]), (Bigint, Set(Bigint))));
+    }
+
+    #[test]
+    fn nulls_are_equal() {
+        assert!(Value::null().eq_unordered(&Value::null(), Map(Bigint, Text)));
+        assert!(!Value::null().eq_unordered(&Value::map(vec![(1, 1)]), Map(Bigint, Bigint)));
+    }
+
+    #[test]
+    fn compare_scalar_ints() {
+        let one = Value::bigint(1);
+        let two = Value::bigint(2);
+        assert_eq!(one.partial_cmp_scalar(&two), Some(Ordering::Less));
+        assert_eq!(two.partial_cmp_scalar(&one), Some(Ordering::Greater));
+        assert_eq!(
+            one.partial_cmp_scalar(&Value::bigint(1)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            Value::bigint(-1).partial_cmp_scalar(&one),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[test]
+    fn compare_scalar_strings_and_floats() {
+        let a = Value::string("apple");
+        let b = Value::string("banana");
+        assert_eq!(a.partial_cmp_scalar(&b), Some(Ordering::Less));
+        assert_eq!(
+            Value::double(2.5).partial_cmp_scalar(&Value::double(1.0)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            Value::double(f64::NAN).partial_cmp_scalar(&Value::double(1.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn mismatched_and_non_scalar_values_are_not_comparable() {
+        assert_eq!(
+            Value::bigint(1).partial_cmp_scalar(&Value::string("1")),
+            None
+        );
+        assert_eq!(
+            Value::bigint(1).partial_cmp_scalar(&Value::double(1.0)),
+            None
+        );
+        assert_eq!(Value::null().partial_cmp_scalar(&Value::null()), None);
+        assert_eq!(Value::bigint(1).partial_cmp_scalar(&Value::null()), None);
+        let list = Value::list(vec![1]);
+        assert_eq!(list.partial_cmp_scalar(&list), None);
+    }
+}
@@ -0,0 +1,287 @@
+//! Conversion of result sets into [`polars`] data frames.
+//!
+//! Requires the `polars` feature.
+//!
+//! Each column of the result set becomes one typed column of the data frame.
+//! The type of the data frame column is derived from the CQL type reported by
+//! the server in the [`ColumnSpec`](crate::proto::ColumnSpec) of the column:
+//!
+//! | CQL type                                          | Polars type |
+//! |---------------------------------------------------|-------------|
+//! | `tinyint`, `smallint`, `int`, `bigint`, `counter` | `Int64`     |
+//! | `timestamp` (milliseconds since Unix epoch)       | `Int64`     |
+//! | `time` (nanoseconds since midnight)               | `Int64`     |
+//! | `float`                                           | `Float32`   |
+//! | `double`                                          | `Float64`   |
+//! | `boolean`                                         | `Boolean`   |
+//! | `ascii`, `text`, `varchar`                        | `String`    |
+//! | `blob`                                            | `Binary`    |
+//!
+//! CQL `null` values become polars nulls.
+//!
+//! Other CQL types, e.g. `uuid`, `decimal`, `date`, collections, tuples and
+//! user defined types, are not supported and make the conversion fail with
+//! [`DataFrameError::UnsupportedColumnType`]. Cast such columns to a supported
+//! type in the query, e.g. `SELECT CAST(id AS text) ...`, if you need them in a data frame.
+//!
+//! # Example
+//! ```no_run
+//! use std::convert::TryFrom;
+//! use stargate_grpc::{Query, ResultSet, StargateClient};
+//!
+//! # async fn run(mut client: StargateClient) -> anyhow::Result<()> {
+//! let query = Query::builder()
+//!     .keyspace("ks")
+//!     .query("SELECT id, login FROM users")
+//!     .build();
+//!
+//! let result_set = ResultSet::try_from(client.execute_query(query).await?)?;
+//! let frame = result_set.into_dataframe()?;
+//! println!("{}", frame);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use num_traits::FromPrimitive;
+use polars::prelude::{Column, DataFrame, PolarsError};
+
+use crate::error::ConversionError;
+use crate::from_value::TryFromValue;
+use crate::proto::type_spec::Basic;
+use crate::proto::{ColumnSpec, ResultSet, Value};
+use crate::result::ColumnType;
+
+/// Error returned when a [`ResultSet`] cannot be converted into a [`DataFrame`].
+#[derive(Debug)]
+pub enum DataFrameError {
+    /// When the CQL type of a column has no polars counterpart
+    /// or the server did not report the type of the column.
+    UnsupportedColumnType(String),
+    /// When a value does not match the type declared for its column.
+    Conversion(ConversionError),
+    /// When polars refused to build the data frame, e.g. because of duplicate column names.
+    Polars(PolarsError),
+}
+
+impl From<ConversionError> for DataFrameError {
+    fn from(error: ConversionError) -> Self {
+        DataFrameError::Conversion(error)
+    }
+}
+
+impl From<PolarsError> for DataFrameError {
+    fn from(error: PolarsError) -> Self {
+        DataFrameError::Polars(error)
+    }
+}
+
+impl Display for DataFrameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataFrameError::UnsupportedColumnType(column) => {
+                write!(
+                    f,
+                    "Column {} has a type not supported in data frames",
+                    column
+                )
+            }
+            DataFrameError::Conversion(error) => Display::fmt(error, f),
+            DataFrameError::Polars(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl Error for DataFrameError {}
+
+impl ResultSet {
+    /// Converts the rows of this result set into a polars [`DataFrame`].
+    ///
+    /// See the [module documentation](crate::dataframe) for the supported column types.
+    pub fn into_dataframe(self) -> Result<DataFrame, DataFrameError> {
+        let mut rows: Vec<Vec<Value>> = self.rows.into_iter().map(|row| row.values).collect();
+        let columns = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, spec)| {
+                let values = rows
+                    .iter_mut()
+                    .map(|row| row.get_mut(index).map(std::mem::take).unwrap_or_default());
+                into_column(spec, values)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(DataFrame::new(columns)?)
+    }
+}
+
+fn into_column(
+    spec: &ColumnSpec,
+    values: impl Iterator<Item = Value>,
+) -> Result<Column, DataFrameError> {
+    let name = spec.name.as_str().into();
+    let basic = match ColumnType::from(spec.r#type.as_ref()) {
+        ColumnType::Basic(basic) => Some(basic),
+        _ => None,
+    };
+    let column = match basic {
+        Some(Basic::Tinyint)
+        | Some(Basic::Smallint)
+        | Some(Basic::Int)
+        | Some(Basic::Bigint)
+        | Some(Basic::Counter)
+        | Some(Basic::Timestamp) => Column::new(name, convert_all::<i64>(values)?),
+        Some(Basic::Time) => Column::new(name, convert_time(values)?),
+        Some(Basic::Float) => Column::new(name, convert_all::<f32>(values)?),
+        Some(Basic::Double) => Column::new(name, convert_all::<f64>(values)?),
+        Some(Basic::Boolean) => Column::new(name, convert_all::<bool>(values)?),
+        Some(Basic::Ascii) | Some(Basic::Text) | Some(Basic::Varchar) => {
+            Column::new(name, convert_all::<String>(values)?)
+        }
+        Some(Basic::Blob) => Column::new(name, convert_all::<Vec<u8>>(values)?),
+        _ => return Err(DataFrameError::UnsupportedColumnType(spec.name.clone())),
+    };
+    Ok(column)
+}
+
+fn convert_all<T: TryFromValue>(
+    values: impl Iterator<Item = Value>,
+) -> Result<Vec<Option<T>>, ConversionError> {
+    values.map(TryFromValue::try_from).collect()
+}
+
+/// Reads `time` values, stored as unsigned nanoseconds since midnight, as `i64`.
+fn convert_time(values: impl Iterator<Item = Value>) -> Result<Vec<Option<i64>>, ConversionError> {
+    values
+        .map(|value| {
+            let nanos: Option<u64> = TryFromValue::try_from(value)?;
+            nanos
+                .map(|n| i64::from_u64(n).ok_or_else(|| ConversionError::out_of_range::<_, i64>(n)))
+                .transpose()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use polars::prelude::DataType;
+
+    use super::*;
+    use crate::proto::type_spec::Spec;
+    use crate::proto::{Row, TypeSpec};
+
+    fn column(name: &str, basic: Basic) -> ColumnSpec {
+        ColumnSpec {
+            r#type: Some(TypeSpec {
+                spec: Some(Spec::Basic(basic as i32)),
+            }),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn convert_result_set_into_dataframe() {
+        let result_set = ResultSet {
+            columns: vec![
+                column("id", Basic::Bigint),
+                column("login", Basic::Varchar),
+                column("score", Basic::Double),
+                column("active", Basic::Boolean),
+            ],
+            rows: vec![
+                Row {
+                    values: vec![
+                        Value::bigint(1),
+                        Value::string("user_1"),
+                        Value::double(0.5),
+                        Value::boolean(true),
+                    ],
+                },
+                Row {
+                    values: vec![
+                        Value::bigint(2),
+                        Value::null(),
+                        Value::double(1.5),
+                        Value::boolean(false),
+                    ],
+                },
+            ],
+            paging_state: None,
+        };
+
+        let frame = result_set.into_dataframe().unwrap();
+        assert_eq!(frame.shape(), (2, 4));
+        assert_eq!(
+            frame.dtypes(),
+            vec![
+                DataType::Int64,
+                DataType::String,
+                DataType::Float64,
+                DataType::Boolean
+            ]
+        );
+        let ids: Vec<_> = frame.column("id").unwrap().i64().unwrap().iter().collect();
+        assert_eq!(ids, vec![Some(1), Some(2)]);
+        let logins: Vec<_> = frame
+            .column("login")
+            .unwrap()
+            .str()
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(logins, vec![Some("user_1"), None]);
+    }
+
+    #[test]
+    fn convert_time_and_timestamp_columns() {
+        let result_set = ResultSet {
+            columns: vec![
+                column("created", Basic::Timestamp),
+                column("opens_at", Basic::Time),
+            ],
+            rows: vec![
+                Row {
+                    values: vec![Value::bigint(1633478400021i64), Value::time(1000)],
+                },
+                Row {
+                    values: vec![Value::null(), Value::null()],
+                },
+            ],
+            paging_state: None,
+        };
+
+        let frame = result_set.into_dataframe().unwrap();
+        assert_eq!(frame.dtypes(), vec![DataType::Int64, DataType::Int64]);
+        let created: Vec<_> = frame
+            .column("created")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(created, vec![Some(1633478400021), None]);
+        let opens_at: Vec<_> = frame
+            .column("opens_at")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(opens_at, vec![Some(1000), None]);
+    }
+
+    #[test]
+    fn reject_unsupported_column_type() {
+        let result_set = ResultSet {
+            columns: vec![column("id", Basic::Uuid)],
+            rows: vec![],
+            paging_state: None,
+        };
+        match result_set.into_dataframe() {
+            Err(DataFrameError::UnsupportedColumnType(column)) => assert_eq!(column, "id"),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Human readable representation of values, resembling CQL literals.
+
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+
+use crate::proto::value::Inner;
+use crate::Value;
+
+/// Formats the value similarly to a CQL literal, e.g. for logging.
+///
+/// Strings are single-quoted, blobs are written in hex with the `0x` prefix,
+/// UUIDs and IP addresses are written in their usual textual forms.
+/// The wire format doesn't tell lists, sets, maps and tuples apart, so all collections
+/// are written as lists of their elements in square brackets. Maps are written as
+/// their keys and values interleaved. Fields of UDT values are sorted by name.
+/// Dates and times are written as the raw numbers sent over the wire.
+///
+/// # Example
+/// ```
+/// use stargate_grpc::Value;
+///
+/// assert_eq!(Value::bigint(1).to_string(), "1");
+/// assert_eq!(Value::string("it's").to_string(), "'it''s'");
+/// assert_eq!(Value::list(vec![1, 2]).to_string(), "[1, 2]");
+/// assert_eq!(Value::udt(vec![("b", 2), ("a", 1)]).to_string(), "{a: 1, b: 2}");
+/// assert_eq!(Value::null().to_string(), "null");
+/// ```
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.inner {
+            None | Some(Inner::Null(_)) => write!(f, "null"),
+            Some(Inner::Unset(_)) => write!(f, "unset"),
+            Some(Inner::Int(x)) => write!(f, "{}", x),
+            Some(Inner::Float(x)) => write!(f, "{:?}", x),
+            Some(Inner::Double(x)) => write!(f, "{:?}", x),
+            Some(Inner::Boolean(x)) => write!(f, "{}", x),
+            Some(Inner::String(x)) => write!(f, "'{}'", x.replace('\'', "''")),
+            Some(Inner::Bytes(x)) => write_hex(f, x),
+            Some(Inner::Inet(x)) => match x.value.len() {
+                4 => write!(
+                    f,
+                    "{}",
+                    IpAddr::from(<[u8; 4]>::try_from(&x.value[..]).unwrap())
+                ),
+                16 => write!(
+                    f,
+                    "{}",
+                    IpAddr::from(<[u8; 16]>::try_from(&x.value[..]).unwrap())
+                ),
+                _ => write_hex(f, &x.value),
+            },
+            Some(Inner::Uuid(x)) if x.value.len() == 16 => {
+                for (i, byte) in x.value.iter().enumerate() {
+                    if i == 4 || i == 6 || i == 8 || i == 10 {
+                        write!(f, "-")?;
+                    }
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            Some(Inner::Uuid(x)) => write_hex(f, &x.value),
+            Some(Inner::Date(x)) => write!(f, "{}", x),
+            Some(Inner::Time(x)) => write!(f, "{}", x),
+            Some(Inner::Collection(x)) => {
+                write!(f, "[")?;
+                for (i, element) in x.elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Some(Inner::Udt(x)) => {
+                let mut fields: Vec<_> = x.fields.iter().collect();
+                fields.sort_by_key(|(name, _)| name.as_str());
+                write!(f, "{{")?;
+                for (i, (name, value)) in fields.into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                write!(f, "}}")
+            }
+            Some(Inner::Varint(x)) => match to_i128(&x.value) {
+                Some(x) => write!(f, "{}", x),
+                None => write_hex(f, &x.value),
+            },
+            Some(Inner::Decimal(x)) => match to_i128(&x.value) {
+                Some(unscaled) => write_decimal(f, unscaled, x.scale),
+                None => {
+                    write_hex(f, &x.value)?;
+                    write!(f, "E-{}", x.scale)
+                }
+            },
+        }
+    }
+}
+
+fn write_hex(f: &mut Formatter<'_>, bytes: &[u8]) -> std::fmt::Result {
+    write!(f, "0x")?;
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+/// Decodes a big-endian two's complement integer, if it fits in 128 bits.
+fn to_i128(bytes: &[u8]) -> Option<i128> {
+    if bytes.is_empty() || bytes.len() > 16 {
+        return None;
+    }
+    let fill = if bytes[0] & 0x80 != 0 { 0xff } else { 0x00 };
+    let mut buf = [fill; 16];
+    buf[16 - bytes.len()..].copy_from_slice(bytes);
+    Some(i128::from_be_bytes(buf))
+}
+
+/// Writes `unscaled * 10^(-scale)` in plain decimal notation.
+fn write_decimal(f: &mut Formatter<'_>, unscaled: i128, scale: u32) -> std::fmt::Result {
+    let digits = unscaled.unsigned_abs().to_string();
+    let scale = scale as usize;
+    let sign = if unscaled < 0 { "-" } else { "" };
+    if scale == 0 {
+        write!(f, "{}{}", sign, digits)
+    } else if digits.len() > scale {
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int, frac)
+    } else {
+        write!(
+            f,
+            "{}0.{}{}",
+            sign,
+            "0".repeat(scale - digits.len()),
+            digits
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Value;
+
+    #[test]
+    fn display_scalars() {
+        assert_eq!(Value::bigint(-5).to_string(), "-5");
+        assert_eq!(Value::double(1.0).to_string(), "1.0");
+        assert_eq!(Value::float(0.5).to_string(), "0.5");
+        assert_eq!(Value::boolean(true).to_string(), "true");
+        assert_eq!(Value::string("foo").to_string(), "'foo'");
+        assert_eq!(Value::bytes(vec![1, 0xab]).to_string(), "0x01ab");
+        assert_eq!(Value::unset().to_string(), "unset");
+        assert_eq!(Value::default().to_string(), "null");
+    }
+
+    #[test]
+    fn display_uuid_and_inet() {
+        let uuid = Value::raw_uuid(&[
+            0x69, 0x41, 0x52, 0x63, 0xa8, 0x26, 0x4b, 0xd0, 0xa0, 0xc1, 0x65, 0x58, 0xc4, 0x00,
+            0xd0, 0x84,
+        ]);
+        assert_eq!(uuid.to_string(), "69415263-a826-4bd0-a0c1-6558c400d084");
+        assert_eq!(Value::inet([127, 0, 0, 1]).to_string(), "127.0.0.1");
+    }
+
+    #[test]
+    fn display_varint_and_decimal() {
+        assert_eq!(Value::raw_varint(vec![0x01, 0x00]).to_string(), "256");
+        assert_eq!(Value::raw_varint(vec![0xff]).to_string(), "-1");
+        let decimal = Value::raw_decimal;
+        assert_eq!(decimal(2, vec![0x30, 0x39]).to_string(), "123.45");
+        assert_eq!(decimal(3, vec![0x05]).to_string(), "0.005");
+        assert_eq!(decimal(1, vec![0xf6]).to_string(), "-1.0");
+        assert_eq!(decimal(0, vec![0x07]).to_string(), "7");
+    }
+
+    #[test]
+    fn display_nested_collections() {
+        let value = Value::list(vec![
+            Value::map(vec![(1, "a")]),
+            Value::udt(vec![("x", Value::null())]),
+        ]);
+        assert_eq!(value.to_string(), "[[1, 'a'], {x: null}]");
+    }
+}
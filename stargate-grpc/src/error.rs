@@ -1,4 +1,4 @@
-//! Errors returned from failed attempts to convert data.
+//! Errors returned from failed attempts to convert data or to execute queries.
 
 use prost::DecodeError;
 use std::fmt::{Debug, Display, Formatter};
@@ -34,6 +34,10 @@ pub enum ConversionErrorKind {
     /// When the converter attempted to decode a binary blob,
     /// but the conversion failed due to invalid data.
     GrpcDecodeError(DecodeError),
+
+    /// When a string value could not be deserialized from JSON.
+    /// Holds the description of the deserialization error.
+    InvalidJson(String),
 }
 
 impl ConversionError {
@@ -71,6 +75,45 @@ impl ConversionError {
     pub fn decode_error<S: Debug, T>(source: S, error: DecodeError) -> ConversionError {
         Self::new::<S, T>(ConversionErrorKind::GrpcDecodeError(error), source)
     }
+
+    pub fn invalid_json<S: Debug, T>(source: S, error: impl Display) -> ConversionError {
+        Self::new::<S, T>(ConversionErrorKind::InvalidJson(error.to_string()), source)
+    }
+
+    /// Returns the name of the Rust type that the value failed to convert to.
+    pub fn target_type(&self) -> &str {
+        &self.target_type_name
+    }
+
+    /// Returns true if the conversion between the types is not defined.
+    pub fn is_incompatible(&self) -> bool {
+        matches!(self.kind, ConversionErrorKind::Incompatible)
+    }
+
+    /// Returns true if the source value is out of range of the target type.
+    pub fn is_out_of_range(&self) -> bool {
+        matches!(self.kind, ConversionErrorKind::OutOfRange)
+    }
+
+    /// Returns true if a required UDT field was missing.
+    pub fn is_field_not_found(&self) -> bool {
+        matches!(self.kind, ConversionErrorKind::FieldNotFound(_))
+    }
+
+    /// Returns true if a vector or a tuple had a different number of elements than expected.
+    pub fn is_wrong_number_of_items(&self) -> bool {
+        matches!(self.kind, ConversionErrorKind::WrongNumberOfItems { .. })
+    }
+
+    /// Returns true if a binary blob could not be decoded.
+    pub fn is_decode_error(&self) -> bool {
+        matches!(self.kind, ConversionErrorKind::GrpcDecodeError(_))
+    }
+
+    /// Returns true if a string value could not be deserialized from JSON.
+    pub fn is_invalid_json(&self) -> bool {
+        matches!(self.kind, ConversionErrorKind::InvalidJson(_))
+    }
 }
 
 impl Display for ConversionError {
@@ -83,6 +126,7 @@ impl Display for ConversionError {
             ConversionErrorKind::WrongNumberOfItems { actual, expected } => {
                 format!("expected {} but got {} items", expected, actual)
             }
+            ConversionErrorKind::InvalidJson(e) => format!("invalid JSON: {}", e),
         };
         write!(
             f,
@@ -91,3 +135,159 @@ impl Display for ConversionError {
         )
     }
 }
+
+/// Error returned when a query could not be executed or its response could not be processed.
+#[derive(Debug)]
+pub enum QueryError {
+    /// When the request failed on the gRPC level, e.g. the connection was lost
+    /// or the server refused to execute the query.
+    Status(tonic::Status),
+    /// When the response could not be converted to the expected type.
+    Conversion(ConversionError),
+}
+
+impl From<tonic::Status> for QueryError {
+    fn from(status: tonic::Status) -> Self {
+        QueryError::Status(status)
+    }
+}
+
+impl From<ConversionError> for QueryError {
+    fn from(error: ConversionError) -> Self {
+        QueryError::Conversion(error)
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::Status(status) => write!(f, "Query failed: {}", status),
+            QueryError::Conversion(error) => write!(f, "Query failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Error returned when a string does not name a valid consistency level.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseConsistencyError(pub String);
+
+impl Display for ParseConsistencyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown consistency level: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseConsistencyError {}
+
+/// Error returned by [`QueryBuilder::build_checked`](crate::query::QueryBuilder::build_checked)
+/// when the way the values are bound doesn't match the bind markers in the query string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BindMarkerMismatch {
+    /// When values are bound by name, but the query uses positional `?` markers only.
+    NamedValuesForPositionalMarkers,
+    /// When values are bound by position, but the query uses named `:name` markers only.
+    PositionalValuesForNamedMarkers,
+}
+
+impl Display for BindMarkerMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindMarkerMismatch::NamedValuesForPositionalMarkers => {
+                write!(
+                    f,
+                    "Values bound by name, but the query uses positional markers"
+                )
+            }
+            BindMarkerMismatch::PositionalValuesForNamedMarkers => {
+                write!(
+                    f,
+                    "Values bound by position, but the query uses named markers"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BindMarkerMismatch {}
+
+/// Error returned by [`Batch::validate`](crate::Batch::validate)
+/// when the batch exceeds the given [`BatchLimits`](crate::query::BatchLimits).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BatchTooLarge {
+    /// When the batch contains more statements than allowed.
+    TooManyStatements { actual: usize, limit: usize },
+    /// When the encoded size of the batch in bytes exceeds the limit.
+    TooManyBytes { actual: usize, limit: usize },
+}
+
+impl Display for BatchTooLarge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchTooLarge::TooManyStatements { actual, limit } => {
+                write!(
+                    f,
+                    "Batch has {} statements, but at most {} are allowed",
+                    actual, limit
+                )
+            }
+            BatchTooLarge::TooManyBytes { actual, limit } => {
+                write!(
+                    f,
+                    "Batch has {} bytes, but at most {} are allowed",
+                    actual, limit
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BatchTooLarge {}
+
+#[cfg(test)]
+mod test {
+    use prost::DecodeError;
+
+    use crate::error::ConversionError;
+    use crate::Value;
+
+    #[test]
+    fn target_type() {
+        let error = ConversionError::incompatible::<_, String>(Value::bigint(1));
+        assert_eq!(error.target_type(), "alloc::string::String");
+    }
+
+    #[test]
+    fn kind_predicates() {
+        let source = Value::bigint(1);
+        let incompatible = ConversionError::incompatible::<_, String>(&source);
+        let out_of_range = ConversionError::out_of_range::<_, i8>(&source);
+        let field_not_found = ConversionError::field_not_found::<_, ()>(&source, "id");
+        let wrong_number = ConversionError::wrong_number_of_items::<_, (i64,)>(&source, 2, 1);
+        let decode_error =
+            ConversionError::decode_error::<_, Value>(&source, DecodeError::new("invalid"));
+        let invalid_json = ConversionError::invalid_json::<_, String>(&source, "invalid");
+
+        assert!(incompatible.is_incompatible());
+        assert!(!incompatible.is_out_of_range());
+        assert!(out_of_range.is_out_of_range());
+        assert!(!out_of_range.is_incompatible());
+        assert!(field_not_found.is_field_not_found());
+        assert!(!field_not_found.is_incompatible());
+        assert!(wrong_number.is_wrong_number_of_items());
+        assert!(!wrong_number.is_out_of_range());
+        assert!(decode_error.is_decode_error());
+        assert!(!decode_error.is_invalid_json());
+        assert!(invalid_json.is_invalid_json());
+        assert!(!invalid_json.is_decode_error());
+    }
+
+    #[test]
+    fn predicates_of_real_conversion_errors() {
+        let result: Result<i8, _> = Value::bigint(1000).try_into();
+        assert!(result.unwrap_err().is_out_of_range());
+        let result: Result<String, _> = Value::bigint(1).try_into();
+        assert!(result.unwrap_err().is_incompatible());
+    }
+}
@@ -23,21 +23,60 @@
 //! gRPC variant  |  Rust types
 //! --------------| --------------------------------------------
 //! `Boolean`     | `bool`
-//! `Bytes`       | `Vec<u8>`
-//! `Inet`        | [`proto::Inet`]
-//! `Int`         | `i64`, `i32`, `i16`, `i8`, `u32`, `u16`
+//! `Bytes`       | `Vec<u8>`, `SmallVec<[u8; N]>`
+//! `Inet`        | [`proto::Inet`], `std::net::IpAddr`
+//! `Int`         | `i64`, `i32`, `i16`, `i8`, `u32`, `u16`, `std::time::Duration`, `DateFromInt`
 //! `Double`      | `f64`
 //! `Date`        | `i32`, `chrono::Date<Local>`, `chrono::Date<Utc>`
 //! `Decimal`     | [`proto::Decimal`]
 //! `Float`       | `f32`
-//! `String`      | `String`
-//! `Time`        | `u64`
-//! `Timestamp`   | `std::time::SystemTime`,`chrono::DateTime<Local>`, `chrono::DateTime<Utc>`
+//! `String`      | `String`, `std::net::IpAddr`, `std::path::PathBuf`, `uuid::Uuid`, `url::Url`
+//! `Time`        | `u64`, `std::time::Duration`, `chrono::NaiveTime`
+//! `Timestamp`   | `std::time::SystemTime`,`chrono::DateTime<Local>`, `chrono::DateTime<Utc>`, `chrono::DateTime<FixedOffset>`, `chrono::NaiveDateTime`
 //! `Uuid`        | [`proto::Uuid`], `uuid::Uuid`
-//! `Udt`         | [`proto::UdtValue`]
+//! `Udt`         | [`proto::UdtValue`], `HashMap<String, V>`, `BTreeMap<String, V>`
 //! `Varint`      | [`proto::Varint`]
 //! `Collection`  | `Vec<T>`, `HashSet<T>`, `BTreeSet<T>`, `HashMap<K, V>`, `BTreeMap<K, V>`, `(T1, T2, ..., Tn)`
 //!
+//! Conversions to narrow integer types check the range of the received value and return
+//! a `ConversionError` of `ConversionErrorKind::OutOfRange` if it doesn't fit.
+//! There is no conversion to `u8`, because `Vec<u8>` is reserved for reading blobs;
+//! read `tinyint` columns into `i8` or `i16` instead.
+//!
+//! A `Duration` is read from a `time` value as the time elapsed since midnight.
+//! It can be also read from a non-negative `Int` value interpreted as a number of nanoseconds,
+//! e.g. a `bigint` column holding a duration measured in nanoseconds.
+//!
+//! An `IpAddr` can be read from both native `inet` columns and addresses stored as text.
+//! An `Inet` value becomes an IPv4 or IPv6 address depending on its length (4 or 16 bytes).
+//! A `String` value is parsed with the standard `IpAddr` parser. The conversion fails
+//! if the bytes have a different length or the text is not a valid address.
+//!
+//! With the `url` feature enabled, a `url::Url` can be read from a URL stored as text.
+//! A string that is not a valid absolute URL fails the conversion with
+//! `ConversionErrorKind::Incompatible`.
+//!
+//! A UDT value can be read into a `HashMap` or a `BTreeMap` keyed by the field names.
+//! Reading it into a map of `Value`s gives access to fields not known at compile time,
+//! e.g. in tools handling arbitrary schemas:
+//!
+//! ```rust
+//! # use stargate_grpc::error::ConversionError;
+//! use std::collections::HashMap;
+//! use stargate_grpc::Value;
+//!
+//! let udt = Value::udt(vec![("street", Value::string("Main")), ("number", Value::bigint(5))]);
+//! let fields: HashMap<String, Value> = udt.try_into()?;
+//! assert_eq!(fields["number"], Value::bigint(5));
+//! # Ok::<(), ConversionError>(())
+//! ```
+//!
+//! Tuples of 1 to 16 elements can be read both from a `Collection` value, e.g. a `tuple` column
+//! or a tuple nested in a `list`, and from a whole [`Row`]. Both follow the same rules:
+//! the number of elements must match the size of the tuple exactly, otherwise
+//! `ConversionErrorKind::WrongNumberOfItems` is returned, and each element is converted
+//! with its own conversion, so null elements require `Option` elements.
+//!
 //! ## Handling nulls
 //!
 //! A `Value` can be a `null` or `unset`. If you try to convert a
@@ -57,8 +96,10 @@
 //!
 //! In order to be able to convert `Value`s into `chrono` dates and timestamps,
 //! add `chrono` crate to dependencies of your project and enable `chrono` feature on this crate.
-//! This crate can create only date and time values in the `chrono::Local` and `chrono::Utc`
-//! timezones.
+//! Dates can be read in the `chrono::Local` and `chrono::Utc` timezones.
+//! Timestamps can be read in these timezones, with a `chrono::FixedOffset`,
+//! or as a `chrono::NaiveDateTime`, as shown below. Other timezones, e.g. from `chrono-tz`,
+//! are not supported directly; read a `DateTime<Utc>` and convert it with `with_timezone`.
 //!
 //! ```rust
 //! # use stargate_grpc::error::ConversionError;
@@ -71,6 +112,57 @@
 //! # Ok::<(), ConversionError>(())
 //! ```
 //!
+//! Timestamps can be also read as `chrono::DateTime<FixedOffset>`.
+//! CQL timestamps don't store any offset, so the returned value always has the UTC offset (+00:00).
+//! Call `with_timezone` on the result to view the same instant at a different offset:
+//!
+//! ```rust
+//! # use stargate_grpc::error::ConversionError;
+//! # use stargate_grpc::Value;
+//! # #[cfg(feature = "chrono")] {
+//! use chrono::{DateTime, FixedOffset};
+//! let timestamp: DateTime<FixedOffset> = Value::bigint(1633478400021_i64).try_into()?;
+//! assert_eq!(timestamp.to_rfc3339(), "2021-10-06T00:00:00.021+00:00");
+//! let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+//! let timestamp = timestamp.with_timezone(&offset);
+//! assert_eq!(timestamp.to_rfc3339(), "2021-10-06T02:00:00.021+02:00");
+//! # }
+//! # Ok::<(), ConversionError>(())
+//! ```
+//!
+//! Reading a timestamp into `chrono::NaiveDateTime` gives the date and time in UTC,
+//! without the timezone information:
+//!
+//! ```rust
+//! # use stargate_grpc::error::ConversionError;
+//! # use stargate_grpc::Value;
+//! # #[cfg(feature = "chrono")] {
+//! use chrono::NaiveDateTime;
+//! let timestamp: NaiveDateTime = Value::bigint(1633478400021_i64).try_into()?;
+//! assert_eq!(timestamp.to_string(), "2021-10-06 00:00:00.021");
+//! # }
+//! # Ok::<(), ConversionError>(())
+//! ```
+//!
+//! A `time` value, which counts nanoseconds since midnight, can be read as `chrono::NaiveTime`.
+//! A value of a day or more is out of range.
+//!
+//! Dates can be stored in two ways. A CQL `date` column holds the native `Date` value,
+//! which reads as `chrono::Date<Utc>` or `chrono::Date<Local>`.
+//! Some schemas keep dates as plain day counts since the Unix epoch in an `int` or `bigint`
+//! column instead. Read those with the [`DateFromInt`] wrapper, which yields a `chrono::NaiveDate`:
+//!
+//! ```rust
+//! # use stargate_grpc::error::ConversionError;
+//! # use stargate_grpc::Value;
+//! # #[cfg(feature = "chrono")] {
+//! use stargate_grpc::from_value::DateFromInt;
+//! let DateFromInt(date) = Value::bigint(18906).try_into()?;
+//! assert_eq!(date.to_string(), "2021-10-06");
+//! # }
+//! # Ok::<(), ConversionError>(())
+//! ```
+//!
 //! ## Converting to `uuid::Uuid`
 //! Similarly a `Value` of UUID type can be converted to `uuid::Uuid` once you enable feature
 //! `uuid`.
@@ -85,6 +177,20 @@
 //! # Ok::<(), ConversionError>(())
 //! ```
 //!
+//! A `uuid::Uuid` can be also read from a UUID stored as text, e.g. in a `text` column.
+//! A `Uuid` value is always read as is; a `String` value is parsed, and a string that is not
+//! a valid UUID fails the conversion with `ConversionErrorKind::Incompatible`.
+//!
+//! ```rust
+//! # use stargate_grpc::error::ConversionError;
+//! # use stargate_grpc::Value;
+//! # #[cfg(feature = "uuid")] {
+//! let uuid: uuid::Uuid = Value::string("69415263-a826-4bd0-a0c1-6558c400d084").try_into()?;
+//! assert_eq!(uuid.as_u128(), 0x69415263_a826_4bd0_a0c16558c400d084);
+//! # }
+//! # Ok::<(), ConversionError>(())
+//! ```
+//!
 //! ## Custom conversions
 //! You can make `Value` convertible to any type by implementing the [`TryFromValue`] trait.
 //!
@@ -111,8 +217,10 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::error::Error;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 use std::iter::FromIterator;
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use itertools::Itertools;
@@ -147,6 +255,100 @@ impl Value {
         T::try_from(self)
     }
 
+    /// Converts a `Double` or an `Int` value to `f64`.
+    ///
+    /// The regular conversion to `f64` accepts only `Double` values, to avoid
+    /// silent precision loss. Use this method to opt in for reading integers as well,
+    /// e.g. results of aggregate functions over integer columns.
+    /// Integers with an absolute value up to 2<sup>53</sup> are converted exactly;
+    /// larger ones are rounded to the nearest representable `f64`.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// assert_eq!(Value::bigint(42).try_into_f64_from_int().unwrap(), 42.0);
+    /// assert_eq!(Value::double(0.5).try_into_f64_from_int().unwrap(), 0.5);
+    /// ```
+    pub fn try_into_f64_from_int(self) -> Result<f64, ConversionError> {
+        match self.inner {
+            Some(value::Inner::Int(x)) => Ok(x as f64),
+            Some(value::Inner::Double(x)) => Ok(x),
+            other => Err(ConversionError::incompatible::<_, f64>(other)),
+        }
+    }
+
+    /// Deserializes a JSON document stored in a string value.
+    ///
+    /// Use it to read documents bound with
+    /// [`QueryBuilder::bind_name_json`](crate::query::QueryBuilder::bind_name_json).
+    /// Requires the `serde` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// let value = Value::string("[1, 2, 3]");
+    /// let numbers: Vec<i32> = value.try_deserialize_json().unwrap();
+    /// assert_eq!(numbers, vec![1, 2, 3]);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn try_deserialize_json<T: serde::de::DeserializeOwned>(
+        self,
+    ) -> Result<T, ConversionError> {
+        match self.inner {
+            Some(value::Inner::String(json)) => serde_json::from_str(&json)
+                .map_err(|e| ConversionError::invalid_json::<_, T>(&json, e)),
+            other => Err(ConversionError::incompatible::<_, T>(other)),
+        }
+    }
+
+    /// Converts each element of a collection independently, without stopping at the
+    /// first element that fails to convert.
+    ///
+    /// Unlike the conversion to `Vec<T>`, which returns only the first error,
+    /// this returns the result of converting every element, in order.
+    /// If the value is not a collection, the returned vector holds a single error.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// let list = Value::list(vec![Value::bigint(1), Value::string("two")]);
+    /// let results = list.try_into_vec_lenient::<i64>();
+    /// assert_eq!(results[0].as_ref().unwrap(), &1);
+    /// assert!(results[1].is_err());
+    /// ```
+    pub fn try_into_vec_lenient<T: TryFromValue>(self) -> Vec<Result<T, ConversionError>> {
+        match self.inner {
+            Some(value::Inner::Collection(c)) => {
+                c.elements.into_iter().map(|e| e.try_into()).collect()
+            }
+            other => vec![Err(ConversionError::incompatible::<_, Vec<T>>(other))],
+        }
+    }
+
+    /// Returns the elements of a collection value without copying them.
+    ///
+    /// Lists, sets and tuples yield their elements in order.
+    /// Maps yield their keys and values interleaved: `key1, value1, key2, value2, ...`.
+    /// Returns `None` if the value is not a collection.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// let list = Value::list(vec![1, 2, 3]);
+    /// assert_eq!(list.as_collection().unwrap()[0], Value::bigint(1));
+    /// assert_eq!(Value::bigint(1).as_collection(), None);
+    /// ```
+    pub fn as_collection(&self) -> Option<&[Value]> {
+        match &self.inner {
+            Some(value::Inner::Collection(c)) => Some(&c.elements),
+            _ => None,
+        }
+    }
+
     /// Moves the value out, and leaves an empty inner slot.
     /// This is useful for taking values out of a vector.
     pub fn take(&mut self) -> Value {
@@ -230,10 +432,23 @@ gen_conversion!(u16; value::Inner::Int(x) => safe_convert_primitive(x));
 gen_conversion!(f32; value::Inner::Float(x) => Ok(x));
 gen_conversion!(f64; value::Inner::Double(x) => Ok(x));
 gen_conversion!(String; value::Inner::String(x) => Ok(x));
+gen_conversion!(PathBuf; value::Inner::String(x) => Ok(PathBuf::from(x)));
+#[cfg(feature = "url")]
+gen_conversion!(url::Url;
+    value::Inner::String(x) => url::Url::parse(&x).map_err(|_| ConversionError::incompatible::<_, url::Url>(x))
+);
 gen_conversion!(Vec<u8>; value::Inner::Bytes(x) => Ok(x));
 
 gen_conversion!(proto::Decimal; value::Inner::Decimal(x) => Ok(x));
 gen_conversion!(proto::Inet; value::Inner::Inet(x) => Ok(x));
+gen_conversion!(IpAddr;
+    value::Inner::Inet(x) => match x.value.len() {
+        4 => Ok(IpAddr::from(<[u8; 4]>::try_from(x.value.as_slice()).unwrap())),
+        16 => Ok(IpAddr::from(<[u8; 16]>::try_from(x.value.as_slice()).unwrap())),
+        _ => Err(ConversionError::incompatible::<_, IpAddr>(x))
+    },
+    value::Inner::String(x) => x.parse().map_err(|_| ConversionError::incompatible::<_, IpAddr>(x))
+);
 gen_conversion!(proto::UdtValue; value::Inner::Udt(x) => Ok(x));
 gen_conversion!(proto::Uuid; value::Inner::Uuid(x) => Ok(x));
 gen_conversion!(proto::Varint; value::Inner::Varint(x) => Ok(x));
@@ -244,11 +459,23 @@ gen_conversion!(uuid::Uuid; value::Inner::Uuid(x) =>
         .map_err(|_| {
             let actual_len = x.value.len();
             ConversionError::wrong_number_of_items::<_, uuid::Uuid>(x, actual_len, 16)
-        })
+        }),
+    value::Inner::String(x) => x.parse().map_err(|_| ConversionError::incompatible::<_, uuid::Uuid>(x))
+);
+
+gen_conversion!(Duration;
+    value::Inner::Time(nanos) => Ok(Duration::from_nanos(nanos)),
+    value::Inner::Int(nanos) => Ok(Duration::from_nanos(safe_convert_primitive(nanos)?))
 );
 
 gen_conversion!(SystemTime; value::Inner::Int(ts) => {
-    Ok(UNIX_EPOCH.checked_add(Duration::from_millis(ts as u64)).unwrap())
+    let offset = Duration::from_millis(ts.unsigned_abs());
+    let time = if ts >= 0 {
+        UNIX_EPOCH.checked_add(offset)
+    } else {
+        UNIX_EPOCH.checked_sub(offset)
+    };
+    time.ok_or_else(|| ConversionError::out_of_range::<_, Self>(ts))
 });
 
 #[cfg(feature = "chrono")]
@@ -269,6 +496,37 @@ gen_conversion!(chrono::DateTime<chrono::Local>; value::Inner::Int(millis) => {
     ))
 });
 
+#[cfg(feature = "chrono")]
+gen_conversion!(chrono::DateTime<chrono::FixedOffset>; value::Inner::Int(millis) => {
+    use chrono::TimeZone;
+    chrono::Utc
+        .timestamp_millis_opt(millis)
+        .single()
+        .map(|timestamp| timestamp.into())
+        .ok_or_else(|| ConversionError::out_of_range::<_, Self>(millis))
+});
+
+#[cfg(feature = "chrono")]
+gen_conversion!(chrono::NaiveDateTime; value::Inner::Int(millis) => {
+    use chrono::TimeZone;
+    chrono::Utc
+        .timestamp_millis_opt(millis)
+        .single()
+        .map(|timestamp| timestamp.naive_utc())
+        .ok_or_else(|| ConversionError::out_of_range::<_, Self>(millis))
+});
+
+#[cfg(feature = "chrono")]
+gen_conversion!(chrono::NaiveTime; value::Inner::Time(nanos) => {
+    const NANOS_PER_SECOND: u64 = 1_000_000_000;
+    u32::from_u64(nanos / NANOS_PER_SECOND)
+        .and_then(|secs| {
+            let nanos = (nanos % NANOS_PER_SECOND) as u32;
+            chrono::NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos)
+        })
+        .ok_or_else(|| ConversionError::out_of_range::<_, Self>(nanos))
+});
+
 #[cfg(feature = "chrono")]
 fn into_naive_date(days: u32) -> Result<chrono::NaiveDate, ConversionError> {
     let days = days as i64 + i32::MIN as i64;
@@ -293,6 +551,37 @@ gen_conversion!(chrono::Date<chrono::Local>; value::Inner::Date(days) => {
     Ok(chrono::Local.from_utc_date(&into_naive_date(days)?))
 });
 
+/// Reads a date stored as a plain integer number of days since the Unix epoch (1970-01-01).
+///
+/// Use it to read dates kept in an `int` or `bigint` column instead of a CQL `date` column.
+/// A value of the native `date` type is not accepted, because it uses a different
+/// representation; read it as `chrono::Date` instead.
+///
+/// # Example
+/// ```
+/// # use stargate_grpc::error::ConversionError;
+/// use stargate_grpc::from_value::DateFromInt;
+/// use stargate_grpc::Value;
+///
+/// let DateFromInt(date) = Value::int(18906).try_into()?;
+/// assert_eq!(date.to_string(), "2021-10-06");
+/// # Ok::<(), ConversionError>(())
+/// ```
+#[cfg(feature = "chrono")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct DateFromInt(pub chrono::NaiveDate);
+
+#[cfg(feature = "chrono")]
+gen_conversion!(DateFromInt; value::Inner::Int(days) => {
+    // number of days from 0001-01-01 to 1970-01-01, counting the first day as 1
+    const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+    i32::from_i64(days)
+        .and_then(|days| days.checked_add(UNIX_EPOCH_DAYS_FROM_CE))
+        .and_then(chrono::NaiveDate::from_num_days_from_ce_opt)
+        .map(DateFromInt)
+        .ok_or_else(|| ConversionError::out_of_range::<_, Self>(days))
+});
+
 /// Counts the number of arguments
 macro_rules! count {
     () => (0usize);
@@ -410,6 +699,9 @@ fn convert_collection<A: TryFromValue, T: FromIterator<A>>(
 /// Converts a `Value` into a vector, converting all elements to appropriate type `T` if needed.
 /// `T` can be any type that have a supported conversion from `Value`.
 /// It is also allowed that `T == Value` so you can get a heterogeneous collection back.
+///
+/// The implementation is generic, but it is compiled separately for each element type,
+/// so reading e.g. a `list<bigint>` into `Vec<i64>` calls no trait methods dynamically.
 impl<T: TryFromValue> TryFromValue for Vec<T> {
     fn try_from(value: Value) -> Result<Self, ConversionError> {
         convert_collection(value)
@@ -455,16 +747,36 @@ where
     }
 }
 
-/// Converts a `Value` representing a map into a hash-map.
-/// Obviously the order is undefined
-impl<K, V> TryFromValue for HashMap<K, V>
+/// Converts the entries of a `Value` representing a map, or the fields of a UDT value,
+/// into key-value pairs.
+/// Field names of a UDT are converted to keys as text values.
+fn map_entries<K, V>(value: Value) -> Result<Vec<KeyValue<K, V>>, ConversionError>
+where
+    K: TryFromValue,
+    V: TryFromValue,
+{
+    match value.inner {
+        Some(value::Inner::Udt(udt)) => udt
+            .fields
+            .into_iter()
+            .map(|(k, v)| Ok(KeyValue(Value::string(k).try_into()?, v.try_into()?)))
+            .collect(),
+        _ => value.try_into(),
+    }
+}
+
+/// Converts a `Value` representing a map or a UDT into a hash-map.
+/// Obviously the order is undefined.
+/// The map can use any hasher that can be created with `Default`.
+impl<K, V, S> TryFromValue for HashMap<K, V, S>
 where
     K: TryFromValue + Eq + Hash,
     V: TryFromValue,
+    S: BuildHasher + Default,
 {
     fn try_from(value: Value) -> Result<Self, ConversionError> {
-        let pairs: Vec<KeyValue<K, V>> = value.try_into()?;
-        let mut map = HashMap::with_capacity(pairs.len());
+        let pairs: Vec<KeyValue<K, V>> = map_entries(value)?;
+        let mut map = HashMap::with_capacity_and_hasher(pairs.len(), S::default());
         map.extend(pairs.into_iter().map(|kv| kv.into_tuple()));
         Ok(map)
     }
@@ -476,7 +788,7 @@ where
     V: TryFromValue,
 {
     fn try_from(value: Value) -> Result<Self, ConversionError> {
-        let pairs: Vec<KeyValue<K, V>> = value.try_into()?;
+        let pairs: Vec<KeyValue<K, V>> = map_entries(value)?;
         let mut map = BTreeMap::new();
         map.extend(pairs.into_iter().map(|kv| kv.into_tuple()));
         Ok(map)
@@ -487,16 +799,72 @@ gen_std_conversion_generic!(<T> Vec<T>);
 gen_std_conversion_generic!(<T> Option<Vec<T>>);
 gen_std_conversion_generic!(<K, V> Vec<KeyValue<K, V>>);
 gen_std_conversion_generic!(<K, V> Option<Vec<KeyValue<K, V>>>);
-gen_std_conversion_generic!(<K: Eq + Hash, V> HashMap<K, V>);
-gen_std_conversion_generic!(<K: Eq + Hash, V> Option<HashMap<K, V>>);
+// Can't use `gen_std_conversion_generic` because the hasher doesn't implement `TryFromValue`:
+impl<K, V, S> TryFrom<Value> for HashMap<K, V, S>
+where
+    K: TryFromValue + Eq + Hash,
+    V: TryFromValue,
+    S: BuildHasher + Default,
+{
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.try_into()
+    }
+}
+
+impl<K, V, S> TryFrom<Value> for Option<HashMap<K, V, S>>
+where
+    K: TryFromValue + Eq + Hash,
+    V: TryFromValue,
+    S: BuildHasher + Default,
+{
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.try_into()
+    }
+}
+
 gen_std_conversion_generic!(<K: Ord, V> BTreeMap<K, V>);
 gen_std_conversion_generic!(<K: Ord, V> Option<BTreeMap<K, V>>);
 
+/// Blobs of up to `N` bytes are copied inline, and larger ones take over the buffer
+/// of the `Value`, so the conversion never allocates.
+#[cfg(feature = "smallvec")]
+impl<const N: usize> TryFromValue for smallvec::SmallVec<[u8; N]> {
+    fn try_from(value: Value) -> Result<Self, ConversionError> {
+        let bytes: Vec<u8> = value.try_into()?;
+        if bytes.len() <= N {
+            Ok(smallvec::SmallVec::from_slice(&bytes))
+        } else {
+            Ok(smallvec::SmallVec::from_vec(bytes))
+        }
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<const N: usize> TryFrom<Value> for smallvec::SmallVec<[u8; N]> {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.try_into()
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<const N: usize> TryFrom<Value> for Option<smallvec::SmallVec<[u8; N]>> {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.try_into()
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::hash_map::DefaultHasher;
     use std::convert::TryInto;
+    use std::hash::BuildHasherDefault;
 
     use super::*;
+    use crate::error::ConversionErrorKind;
 
     #[test]
     fn convert_value_to_i64() {
@@ -576,6 +944,94 @@ mod test {
         assert_eq!(inet, proto::Inet { value: vec![1, 2] })
     }
 
+    #[test]
+    fn convert_int_value_to_f64_on_opt_in() {
+        let v = Value::bigint(-12);
+        assert!(v.clone().try_into::<f64>().is_err());
+        assert_eq!(v.try_into_f64_from_int().unwrap(), -12.0);
+        let v = Value::bigint(1_i64 << 53);
+        assert_eq!(v.try_into_f64_from_int().unwrap(), 9007199254740992.0);
+    }
+
+    #[test]
+    fn convert_double_value_to_f64_on_opt_in() {
+        let v = Value::double(3.5);
+        assert_eq!(v.try_into_f64_from_int().unwrap(), 3.5);
+        assert!(Value::string("3.5").try_into_f64_from_int().is_err());
+        assert!(Value::float(3.5).try_into_f64_from_int().is_err());
+    }
+
+    #[test]
+    fn borrow_collection_elements() {
+        let v = Value::list(vec![Value::bigint(1), Value::string("foo")]);
+        assert_eq!(
+            v.as_collection(),
+            Some(&[Value::bigint(1), Value::string("foo")][..])
+        );
+        assert_eq!(
+            Value::list(Vec::<i64>::new()).as_collection(),
+            Some(&[][..])
+        );
+    }
+
+    #[test]
+    fn borrow_collection_elements_of_scalar() {
+        assert_eq!(Value::bigint(1).as_collection(), None);
+        assert_eq!(Value::null().as_collection(), None);
+    }
+
+    #[test]
+    fn convert_value_to_vec_leniently() {
+        let v = Value::list(vec![
+            Value::bigint(1),
+            Value::string("two"),
+            Value::bigint(3),
+            Value::null(),
+        ]);
+        let results = v.try_into_vec_lenient::<i64>();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap(), &1);
+        assert_eq!(
+            results[1].as_ref().unwrap_err().kind,
+            ConversionErrorKind::Incompatible
+        );
+        assert_eq!(results[2].as_ref().unwrap(), &3);
+        assert!(results[3].is_err());
+    }
+
+    #[test]
+    fn convert_scalar_value_to_vec_leniently() {
+        let results = Value::bigint(1).try_into_vec_lenient::<i64>();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+
+    #[test]
+    fn convert_inet_value_to_ip_addr() {
+        let v4: IpAddr = Value::inet([127, 0, 0, 1]).try_into().unwrap();
+        assert_eq!(v4, IpAddr::from([127, 0, 0, 1]));
+        let v6: IpAddr = Value::inet([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1])
+            .try_into()
+            .unwrap();
+        assert_eq!(v6, "::1".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn convert_string_value_to_ip_addr() {
+        let v4: IpAddr = Value::string("10.0.0.1").try_into().unwrap();
+        assert_eq!(v4, IpAddr::from([10, 0, 0, 1]));
+        let v6: IpAddr = Value::string("fe80::1").try_into().unwrap();
+        assert_eq!(v6, "fe80::1".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn convert_invalid_value_to_ip_addr() {
+        let result: Result<IpAddr, _> = Value::raw_inet(vec![1, 2]).try_into();
+        assert_eq!(result.unwrap_err().kind, ConversionErrorKind::Incompatible);
+        let result: Result<IpAddr, _> = Value::string("localhost").try_into();
+        assert_eq!(result.unwrap_err().kind, ConversionErrorKind::Incompatible);
+    }
+
     #[test]
     fn convert_value_to_decimal() {
         let v = Value::raw_decimal(2, vec![1, 2]);
@@ -616,6 +1072,92 @@ mod test {
         assert_eq!(uuid.as_bytes(), &[1; 16])
     }
 
+    #[test]
+    #[cfg(feature = "url")]
+    fn convert_url_round_trip() {
+        let url = url::Url::parse("https://example.com/path?q=1#top").unwrap();
+        let value = Value::from(url.clone());
+        assert_eq!(value, Value::string("https://example.com/path?q=1#top"));
+        let read: url::Url = value.try_into().unwrap();
+        assert_eq!(read, url);
+
+        let error = Value::string("not a url")
+            .try_into::<url::Url>()
+            .unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::Incompatible);
+        let error = Value::bigint(1).try_into::<url::Url>().unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::Incompatible);
+    }
+
+    #[test]
+    #[cfg(feature = "smallvec")]
+    fn convert_small_vec_blob_round_trip() {
+        use smallvec::SmallVec;
+
+        let inline: SmallVec<[u8; 4]> = SmallVec::from_slice(&[1, 2, 3, 4]);
+        let value = Value::from(inline.clone());
+        assert_eq!(value, Value::bytes(vec![1, 2, 3, 4]));
+        let blob: SmallVec<[u8; 4]> = value.try_into().unwrap();
+        assert!(!blob.spilled());
+        assert_eq!(blob, inline);
+
+        let spilled: SmallVec<[u8; 4]> = SmallVec::from_slice(&[1, 2, 3, 4, 5]);
+        let value = Value::from(spilled.clone());
+        assert_eq!(value, Value::bytes(vec![1, 2, 3, 4, 5]));
+        let blob: SmallVec<[u8; 4]> = value.try_into().unwrap();
+        assert!(blob.spilled());
+        assert_eq!(blob, spilled);
+
+        let blob: Option<SmallVec<[u8; 4]>> = Value::null().try_into().unwrap();
+        assert_eq!(blob, None);
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn convert_text_value_to_uuid_uuid() {
+        let v = Value::string("01010101-0101-0101-0101-010101010101");
+        let uuid: uuid::Uuid = v.try_into().unwrap();
+        assert_eq!(uuid.as_bytes(), &[1; 16]);
+
+        let v = Value::string("not a uuid");
+        let error = v.try_into::<uuid::Uuid>().unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::Incompatible);
+    }
+
+    #[test]
+    fn convert_lists_of_one_type() {
+        let ints: Vec<i64> = Value::list(vec![1, 2, 3]).try_into().unwrap();
+        assert_eq!(ints, vec![1, 2, 3]);
+        let strings: Vec<String> = Value::list(vec!["a", "b"]).try_into().unwrap();
+        assert_eq!(strings, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn reject_list_with_element_of_other_type() {
+        let list = Value::list(vec![Value::bigint(1), Value::string("two")]);
+        let error = list.try_into::<Vec<i64>>().unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::Incompatible);
+    }
+
+    #[test]
+    fn convert_udt_value_to_dynamic_map() {
+        let udt = Value::udt(vec![
+            ("login", Value::string("admin")),
+            ("emails", Value::list(vec!["admin@example.net"])),
+        ]);
+        let fields: HashMap<String, Value> = udt.clone().try_into().unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields["login"], Value::string("admin"));
+        assert_eq!(fields["emails"], Value::list(vec!["admin@example.net"]));
+
+        let fields: BTreeMap<String, Value> = udt.try_into().unwrap();
+        assert_eq!(fields.keys().collect_vec(), vec!["emails", "login"]);
+
+        let udt = Value::udt(vec![("a", 1), ("b", 2)]);
+        let fields: HashMap<String, i64> = udt.try_into().unwrap();
+        assert_eq!(fields["b"], 2);
+    }
+
     #[test]
     fn convert_value_to_system_time() {
         let v = Value::bigint(10000);
@@ -623,6 +1165,40 @@ mod test {
         assert_eq!(time.duration_since(UNIX_EPOCH).unwrap().as_millis(), 10000);
     }
 
+    #[test]
+    fn convert_negative_value_to_system_time() {
+        let time: SystemTime = Value::bigint(-1500).try_into().unwrap();
+        assert_eq!(time, UNIX_EPOCH - Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn convert_string_value_to_path() {
+        let path: PathBuf = Value::string("/var/lib/cassandra").try_into().unwrap();
+        assert_eq!(path, PathBuf::from("/var/lib/cassandra"));
+    }
+
+    #[test]
+    fn convert_time_value_to_duration() {
+        let midnight: Duration = Value::time(0).try_into().unwrap();
+        assert_eq!(midnight, Duration::ZERO);
+
+        let noon_nanos = 12 * 3600 * 1_000_000_000_u64 + 30;
+        let noon: Duration = Value::time(noon_nanos).try_into().unwrap();
+        assert_eq!(
+            noon,
+            Duration::from_secs(12 * 3600) + Duration::from_nanos(30)
+        );
+    }
+
+    #[test]
+    fn convert_bigint_value_to_duration() {
+        let duration: Duration = Value::bigint(1_500_000_000).try_into().unwrap();
+        assert_eq!(duration, Duration::from_millis(1500));
+
+        let error = Value::bigint(-1).try_into::<Duration>().unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::OutOfRange);
+    }
+
     #[test]
     #[cfg(feature = "chrono")]
     fn convert_value_to_chrono_date_time() {
@@ -631,6 +1207,77 @@ mod test {
         assert_eq!(time.timestamp_millis(), 10000);
     }
 
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn convert_value_to_chrono_date_time_with_fixed_offset() {
+        let v = Value::bigint(1633478400021_i64);
+        let time: chrono::DateTime<chrono::FixedOffset> = v.try_into().unwrap();
+        assert_eq!(time.timestamp_millis(), 1633478400021);
+        assert_eq!(time.offset().local_minus_utc(), 0);
+        assert_eq!(time.to_rfc3339(), "2021-10-06T00:00:00.021+00:00");
+
+        let v = Value::bigint(-1);
+        let time: chrono::DateTime<chrono::FixedOffset> = v.try_into().unwrap();
+        assert_eq!(time.timestamp_millis(), -1);
+
+        let error = Value::bigint(i64::MAX)
+            .try_into::<chrono::DateTime<chrono::FixedOffset>>()
+            .unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::OutOfRange);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn convert_value_to_chrono_naive_date_time() {
+        let v = Value::bigint(1633478400021_i64);
+        let time: chrono::NaiveDateTime = v.try_into().unwrap();
+        assert_eq!(time.to_string(), "2021-10-06 00:00:00.021");
+
+        let v = Value::bigint(-1);
+        let time: chrono::NaiveDateTime = v.try_into().unwrap();
+        assert_eq!(time.to_string(), "1969-12-31 23:59:59.999");
+
+        let error = Value::bigint(i64::MIN)
+            .try_into::<chrono::NaiveDateTime>()
+            .unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::OutOfRange);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn convert_time_value_to_chrono_naive_time() {
+        let time: chrono::NaiveTime = Value::time(0).try_into().unwrap();
+        assert_eq!(time.to_string(), "00:00:00");
+        let nanos = (13 * 3600 + 45 * 60 + 30) * 1_000_000_000 + 123_000_000;
+        let time: chrono::NaiveTime = Value::time(nanos).try_into().unwrap();
+        assert_eq!(time.to_string(), "13:45:30.123");
+
+        let error = Value::time(24 * 3600 * 1_000_000_000)
+            .try_into::<chrono::NaiveTime>()
+            .unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::OutOfRange);
+        let error = Value::time(u64::MAX)
+            .try_into::<chrono::NaiveTime>()
+            .unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::OutOfRange);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn convert_int_value_to_date_from_int() {
+        let DateFromInt(date) = Value::int(18906).try_into().unwrap();
+        assert_eq!(date.to_string(), "2021-10-06");
+        let DateFromInt(date) = Value::int(-1).try_into().unwrap();
+        assert_eq!(date.to_string(), "1969-12-31");
+
+        let error = Value::bigint(i64::MAX)
+            .try_into::<DateFromInt>()
+            .unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::OutOfRange);
+        let error = Value::date(0).try_into::<DateFromInt>().unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::Incompatible);
+    }
+
     #[test]
     #[cfg(feature = "chrono")]
     fn convert_value_to_chrono_date() {
@@ -709,6 +1356,20 @@ mod test {
         assert_eq!(map.get(&1), Some("foo".to_string()).as_ref());
     }
 
+    #[test]
+    fn convert_value_to_hash_map_with_custom_hasher() {
+        type Hasher = BuildHasherDefault<DefaultHasher>;
+        let v = Value::map(vec![(1, "foo"), (2, "bar")]);
+        let map: HashMap<i64, String, Hasher> = v.try_into().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&"foo".to_string()));
+        assert_eq!(map.get(&2), Some(&"bar".to_string()));
+
+        let v = Value::null();
+        let map: Option<HashMap<i64, String, Hasher>> = v.try_into().unwrap();
+        assert_eq!(map, None);
+    }
+
     #[test]
     fn convert_value_to_btree_map() {
         let v1 = Value::bigint(1);
@@ -766,6 +1427,137 @@ mod test {
         assert!(v.try_into::<String>().is_err());
     }
 
+    #[test]
+    fn convert_value_to_tuple_of_options_with_null_elements() {
+        let timestamp = UNIX_EPOCH + Duration::from_millis(1000);
+        let v = Value::list(vec![Value::bigint(1), Value::null(), Value::raw_int(1000)]);
+        let tuple: (Option<i64>, Option<String>, Option<SystemTime>) = v.try_into().unwrap();
+        assert_eq!(tuple, (Some(1), None, Some(timestamp)));
+
+        let v = Value::list(vec![Value::null(), Value::string("foo"), Value::null()]);
+        let tuple: (Option<i64>, Option<String>, Option<SystemTime>) = v.try_into().unwrap();
+        assert_eq!(tuple, (None, Some("foo".to_string()), None));
+
+        let v = Value::list(vec![Value::null(), Value::null(), Value::null()]);
+        let tuple: (Option<i64>, Option<String>, Option<SystemTime>) = v.try_into().unwrap();
+        assert_eq!(tuple, (None, None, None));
+    }
+
+    #[test]
+    fn null_element_of_tuple_requires_option() {
+        let v = Value::list(vec![Value::bigint(1), Value::null()]);
+        let result = v.try_into::<(Option<i64>, String)>();
+        assert_eq!(result.unwrap_err().kind, ConversionErrorKind::Incompatible);
+    }
+
+    /// The largest tuple supported, as returned from a table with 16 columns
+    /// or read from a `tuple<...>` with 16 elements.
+    type Tuple16 = (
+        i64,
+        i32,
+        i16,
+        i8,
+        u32,
+        u16,
+        f64,
+        f32,
+        bool,
+        String,
+        Vec<u8>,
+        Option<i64>,
+        Vec<i64>,
+        (i64, String),
+        Option<String>,
+        u64,
+    );
+
+    fn tuple16_values() -> Vec<Value> {
+        vec![
+            Value::bigint(1),
+            Value::int(2),
+            Value::smallint(3_i16),
+            Value::tinyint(4_i8),
+            Value::bigint(5),
+            Value::bigint(6),
+            Value::double(7.0),
+            Value::float(8.0),
+            Value::boolean(true),
+            Value::string("ten"),
+            Value::bytes(vec![11]),
+            Value::null(),
+            Value::list(vec![13, 13]),
+            Value::list(vec![Value::bigint(14), Value::string("fourteen")]),
+            Value::string("fifteen"),
+            Value::time(16),
+        ]
+    }
+
+    fn assert_tuple16(t: Tuple16) {
+        assert_eq!((t.0, t.1, t.2, t.3, t.4, t.5), (1, 2, 3, 4, 5, 6));
+        assert_eq!((t.6, t.7, t.8), (7.0, 8.0, true));
+        assert_eq!((t.9, t.10, t.11), ("ten".to_string(), vec![11], None));
+        assert_eq!((t.12, t.13), (vec![13, 13], (14, "fourteen".to_string())));
+        assert_eq!((t.14, t.15), (Some("fifteen".to_string()), 16));
+    }
+
+    #[test]
+    fn convert_value_to_tuple_of_16_elements() {
+        let tuple: Tuple16 = Value::list(tuple16_values()).try_into().unwrap();
+        assert_tuple16(tuple);
+    }
+
+    #[test]
+    fn convert_list_of_tuples_of_16_elements() {
+        let tuple = Value::list(tuple16_values());
+        let list = Value::list(vec![tuple.clone(), tuple]);
+        let tuples: Vec<Tuple16> = list.try_into().unwrap();
+        assert_eq!(tuples.len(), 2);
+        tuples.into_iter().for_each(assert_tuple16);
+
+        // the same list read from a row column:
+        let tuple = Value::list(tuple16_values());
+        let list = Value::list(vec![tuple.clone(), tuple]);
+        let mut row = Row {
+            values: vec![Value::bigint(0), list],
+        };
+        let tuples: Vec<Tuple16> = row.try_take(1).unwrap();
+        assert_eq!(tuples.len(), 2);
+        tuples.into_iter().for_each(assert_tuple16);
+    }
+
+    #[test]
+    fn convert_row_to_tuple_of_16_elements() {
+        let row = Row {
+            values: tuple16_values(),
+        };
+        let tuple: Tuple16 = row.try_into().unwrap();
+        assert_tuple16(tuple);
+    }
+
+    #[test]
+    fn unexpected_tuple_size_in_row_and_value() {
+        let mut values = tuple16_values();
+        values.pop();
+        let result: Result<Tuple16, _> = Value::list(values.clone()).try_into();
+        let error = result.err().unwrap();
+        assert_eq!(
+            error.kind,
+            ConversionErrorKind::WrongNumberOfItems {
+                actual: 15,
+                expected: 16
+            }
+        );
+        let result: Result<Tuple16, _> = Row { values }.try_into();
+        let error = result.err().unwrap();
+        assert_eq!(
+            error.kind,
+            ConversionErrorKind::WrongNumberOfItems {
+                actual: 15,
+                expected: 16
+            }
+        );
+    }
+
     #[test]
     fn unexpected_tuple_size() {
         let v1 = Value::bigint(1);
@@ -818,6 +1610,16 @@ mod test {
         assert_eq!(c, "foo".to_string());
     }
 
+    #[test]
+    fn convert_row_to_tuple_of_options_with_null_elements() {
+        let values = vec![Value::null(), Value::string("foo"), Value::raw_int(1000)];
+        let row = Row { values };
+        let (a, b, c): (Option<i64>, Option<String>, Option<SystemTime>) = row.try_into().unwrap();
+        assert_eq!(a, None);
+        assert_eq!(b, Some("foo".to_string()));
+        assert_eq!(c, Some(UNIX_EPOCH + Duration::from_millis(1000)));
+    }
+
     #[test]
     fn convert_single_item_of_a_row() {
         let values = vec![Value::bigint(1), Value::double(2.0), Value::string("foo")];
@@ -0,0 +1,266 @@
+//! Hashing values and using them as keys of hash maps.
+//!
+//! [`Value`] implements [`Hash`], but it can't be used as a key of a `HashMap` directly,
+//! because it doesn't implement [`Eq`]: floating point values are not reflexive.
+//! Additionally, its derived `PartialEq` distinguishes a value with no inner value
+//! (e.g. `Value::default()`) from an explicit [`Value::null`], although both mean null.
+//!
+//! Wrap values in [`ValueKey`] to use them as keys. Keys are compared after normalization:
+//! - a value with no inner value is equal to `Value::null()`,
+//! - `0.0` is equal to `-0.0`,
+//! - all NaNs are equal to each other,
+//! - fields of UDT values are compared regardless of their order.
+//!
+//! The `Hash` implementation of `Value` applies the same normalization,
+//! so it is consistent both with `PartialEq` of `Value` and with `Eq` of `ValueKey`.
+//!
+//! # Example
+//! ```
+//! use std::collections::HashMap;
+//! use stargate_grpc::Value;
+//! use stargate_grpc::hash::ValueKey;
+//!
+//! let mut cache = HashMap::new();
+//! cache.insert(ValueKey(Value::null()), "null");
+//! assert_eq!(cache.get(&ValueKey(Value::default())), Some(&"null"));
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::proto::value::Inner;
+use crate::Value;
+
+/// A [`Value`] that can be used as a key of a hash map or an element of a hash set.
+///
+/// See the [module documentation](crate::hash) for the rules of comparing keys.
+#[derive(Clone, Debug, Default)]
+pub struct ValueKey(pub Value);
+
+impl From<Value> for ValueKey {
+    fn from(value: Value) -> Self {
+        ValueKey(value)
+    }
+}
+
+impl From<ValueKey> for Value {
+    fn from(key: ValueKey) -> Self {
+        key.0
+    }
+}
+
+impl PartialEq for ValueKey {
+    fn eq(&self, other: &Self) -> bool {
+        normalized_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ValueKey {}
+
+impl Hash for ValueKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self.inner {
+            None | Some(Inner::Null(_)) => state.write_u8(0),
+            Some(Inner::Unset(_)) => state.write_u8(1),
+            Some(Inner::Int(x)) => {
+                state.write_u8(2);
+                x.hash(state)
+            }
+            Some(Inner::Float(x)) => {
+                state.write_u8(3);
+                normalized_f32_bits(*x).hash(state)
+            }
+            Some(Inner::Double(x)) => {
+                state.write_u8(4);
+                normalized_f64_bits(*x).hash(state)
+            }
+            Some(Inner::Boolean(x)) => {
+                state.write_u8(5);
+                x.hash(state)
+            }
+            Some(Inner::String(x)) => {
+                state.write_u8(6);
+                x.hash(state)
+            }
+            Some(Inner::Bytes(x)) => {
+                state.write_u8(7);
+                x.hash(state)
+            }
+            Some(Inner::Inet(x)) => {
+                state.write_u8(8);
+                x.value.hash(state)
+            }
+            Some(Inner::Uuid(x)) => {
+                state.write_u8(9);
+                x.value.hash(state)
+            }
+            Some(Inner::Date(x)) => {
+                state.write_u8(10);
+                x.hash(state)
+            }
+            Some(Inner::Time(x)) => {
+                state.write_u8(11);
+                x.hash(state)
+            }
+            Some(Inner::Collection(x)) => {
+                state.write_u8(12);
+                x.elements.hash(state)
+            }
+            Some(Inner::Udt(x)) => {
+                // The order of fields in a hash map is unspecified,
+                // so combine the hashes of the fields with a commutative operation:
+                state.write_u8(13);
+                state.write_usize(x.fields.len());
+                let mut fields_hash: u64 = 0;
+                for field in &x.fields {
+                    let mut hasher = DefaultHasher::new();
+                    field.hash(&mut hasher);
+                    fields_hash = fields_hash.wrapping_add(hasher.finish());
+                }
+                state.write_u64(fields_hash)
+            }
+            Some(Inner::Varint(x)) => {
+                state.write_u8(14);
+                x.value.hash(state)
+            }
+            Some(Inner::Decimal(x)) => {
+                state.write_u8(15);
+                x.scale.hash(state);
+                x.value.hash(state)
+            }
+        }
+    }
+}
+
+/// Returns the bits of a float, with all zeros and all NaNs mapped to the same bits.
+fn normalized_f32_bits(x: f32) -> u32 {
+    if x == 0.0 {
+        0
+    } else if x.is_nan() {
+        f32::NAN.to_bits()
+    } else {
+        x.to_bits()
+    }
+}
+
+/// Returns the bits of a double, with all zeros and all NaNs mapped to the same bits.
+fn normalized_f64_bits(x: f64) -> u64 {
+    if x == 0.0 {
+        0
+    } else if x.is_nan() {
+        f64::NAN.to_bits()
+    } else {
+        x.to_bits()
+    }
+}
+
+/// Compares values following the rules described in the [module documentation](crate::hash).
+fn normalized_eq(a: &Value, b: &Value) -> bool {
+    match (&a.inner, &b.inner) {
+        (None | Some(Inner::Null(_)), None | Some(Inner::Null(_))) => true,
+        (Some(Inner::Float(x)), Some(Inner::Float(y))) => {
+            normalized_f32_bits(*x) == normalized_f32_bits(*y)
+        }
+        (Some(Inner::Double(x)), Some(Inner::Double(y))) => {
+            normalized_f64_bits(*x) == normalized_f64_bits(*y)
+        }
+        (Some(Inner::Collection(x)), Some(Inner::Collection(y))) => {
+            x.elements.len() == y.elements.len()
+                && x.elements
+                    .iter()
+                    .zip(&y.elements)
+                    .all(|(x, y)| normalized_eq(x, y))
+        }
+        (Some(Inner::Udt(x)), Some(Inner::Udt(y))) => {
+            x.fields.len() == y.fields.len()
+                && x.fields.iter().all(|(name, x)| match y.fields.get(name) {
+                    Some(y) => normalized_eq(x, y),
+                    None => false,
+                })
+        }
+        (x, y) => x == y,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{HashMap, HashSet};
+    use std::hash::{Hash, Hasher};
+
+    use crate::hash::ValueKey;
+    use crate::Value;
+
+    fn hash(value: &Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn none_and_null_collide() {
+        let mut map = HashMap::new();
+        map.insert(ValueKey(Value::null()), 1);
+        map.insert(ValueKey(Value::default()), 2);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map[&ValueKey(Value::null())], 2);
+    }
+
+    #[test]
+    fn nested_none_and_null_collide() {
+        let mut map = HashMap::new();
+        map.insert(
+            ValueKey(Value::list(vec![Value::bigint(1), Value::null()])),
+            1,
+        );
+        map.insert(
+            ValueKey(Value::list(vec![Value::bigint(1), Value::default()])),
+            2,
+        );
+        map.insert(ValueKey(Value::udt(vec![("a", Value::null())])), 3);
+        map.insert(ValueKey(Value::udt(vec![("a", Value::default())])), 4);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn different_values_do_not_collide() {
+        let keys: HashSet<_> = vec![
+            Value::null(),
+            Value::unset(),
+            Value::bigint(0),
+            Value::double(0.0),
+            Value::string(""),
+            Value::list(Vec::<Value>::new()),
+            Value::list(vec![Value::null()]),
+        ]
+        .into_iter()
+        .map(ValueKey)
+        .collect();
+        assert_eq!(keys.len(), 7);
+    }
+
+    #[test]
+    fn normalize_floats() {
+        assert_eq!(ValueKey(Value::double(0.0)), ValueKey(Value::double(-0.0)));
+        assert_eq!(hash(&Value::double(0.0)), hash(&Value::double(-0.0)));
+        assert_eq!(
+            ValueKey(Value::float(f32::NAN)),
+            ValueKey(Value::float(f32::NAN))
+        );
+        assert_ne!(ValueKey(Value::float(1.0)), ValueKey(Value::double(1.0)));
+    }
+
+    #[test]
+    fn hash_of_udt_does_not_depend_on_field_order() {
+        let udt1 = Value::udt(vec![("a", 1), ("b", 2), ("c", 3)]);
+        let udt2 = Value::udt(vec![("c", 3), ("b", 2), ("a", 1)]);
+        assert_eq!(udt1, udt2);
+        assert_eq!(hash(&udt1), hash(&udt2));
+    }
+}
@@ -59,16 +59,18 @@
 //! | `bool`                        | [`types::Boolean`]
 //! | `String`                      | [`types::Text`]
 //! | `&str`                        | [`types::Text`]
+//! | `url::Url`                    | [`types::Text`]
 //! | `std::time::SystemTime`       | [`types::Timestamp`]
 //! | `Vec<u8>`                     | [`types::Blob`], [`types::Varint`]
+//! | `SmallVec<[u8; N]>`           | [`types::Blob`]
 //! | `Vec<T>`                      | [`types::List`]
 //! | `(T1, T2, ...)`               | [`types::List`]
 //! | `HashSet<T>`                  | [`types::Set`]
 //! | `BTreeSet<T>`                 | [`types::Set`]
 //! | `Vec<(K, V)>`                 | [`types::Map`]
 //! | `Vec<KeyValue>`               | [`types::Map`]
-//! | `HashMap<K, V>`               | [`types::Map`]
-//! | `BTreeMap<K, V>`              | [`types::Map`]
+//! | `HashMap<K, V>`               | [`types::Map`], [`types::Udt`]
+//! | `BTreeMap<K, V>`              | [`types::Map`], [`types::Udt`]
 //! | &[u8; 4], [u8; 4]             | [`types::Inet`]
 //! | &[u8; 16], [u8; 16]           | [`types::Inet`]
 //! | &[u8; 16], [u8; 16]           | [`types::Uuid`]
@@ -78,6 +80,13 @@
 //! | [`proto::Uuid`]               | [`types::Uuid`]
 //! | [`proto::Varint`]             | [`types::Varint`]
 //!
+//! Paths may contain sequences that are not valid UTF-8 on some platforms, so they can't be
+//! converted to text infallibly. Use [`Value::try_path`] to convert a path to a text value.
+//!
+//! A `SystemTime` is converted to the number of milliseconds since the Unix epoch, which is
+//! negative for times before 1970. Fractions of a millisecond are rounded down, towards
+//! the past. Times too far from the epoch to fit in an `i64` are clamped to its bounds.
+//!
 //! ## Optional conversions
 //!
 //! The following conversions are provided by features `chrono` and `uuid`:
@@ -86,7 +95,7 @@
 //! |-----------------------------|------------------------------------
 //! | `chrono::Date<T>`           | [`types::Date`]
 //! | `chrono::DateTime<T>`       | [`types::Timestamp`]
-//! | `uuid::Uuid`                | [`types::Uuid`]
+//! | `uuid::Uuid`                | [`types::Uuid`], [`types::Timeuuid`]
 //!
 //!
 //! ## Collections
@@ -129,6 +138,27 @@
 //! assert_ne!(value_as_map, value_as_list)
 //! ```
 //!
+//! ## User defined types
+//!
+//! A `HashMap` or `BTreeMap` with string keys could represent either a CQL `map`
+//! or a value of a user defined type, with the keys being the field names.
+//! Because the default target type of a map is [`types::Map`], a plain conversion
+//! always creates a CQL map. Specify [`types::Udt`] as the target type to create a UDT value
+//! instead:
+//!
+//!```rust
+//! use std::collections::HashMap;
+//! use stargate_grpc::{types, Value};
+//!
+//! let mut fields = HashMap::new();
+//! fields.insert("street", Value::string("Main St."));
+//! fields.insert("number", Value::int(5));
+//!
+//! let address = Value::of_type(types::Udt, fields.clone());
+//! assert_eq!(address, Value::udt(fields.clone()));
+//! assert_ne!(address, Value::map(fields));
+//! ```
+//!
 //! ## Converting from `chrono::Date` and `chrono::DateTime`
 //!
 //! In order to be able to convert `chrono` dates and timestamps into `Value`,
@@ -156,6 +186,24 @@
 //! # }
 //!```
 //!
+//! A `timeuuid` column can store only version 1 UUIDs. Time-sortable UUIDs of
+//! newer versions, e.g. v6 or v7, have a different layout, so they must be stored
+//! in a `uuid` column instead. Converting to [`types::Uuid`] accepts UUIDs of any version,
+//! but converting to [`types::Timeuuid`] panics if the UUID is not a version 1 UUID.
+//! Use [`Value::try_timeuuid`] to get an error instead, e.g. when the UUID comes from user data:
+//!
+//! ```rust
+//! # #[cfg(feature = "uuid")] {
+//! # use stargate_grpc::{types, Value};
+//! let uuid = uuid::Uuid::parse_str("c232ab00-9414-11ec-b3c8-9f6bdeced846").unwrap();
+//! let timeuuid = Value::of_type(types::Timeuuid, uuid);
+//! assert_eq!(Value::try_timeuuid(uuid).unwrap(), timeuuid);
+//!
+//! let v4 = uuid::Uuid::parse_str("936da01f-9abd-4d9d-80c7-02af85c822a8").unwrap();
+//! assert!(Value::try_timeuuid(v4).is_err());
+//! # }
+//!```
+//!
 //! ## Custom conversions
 //! You can make any type convertible to `Value` by implementing the [`IntoValue`] trait.
 //! Use one of `Value::raw_` methods to construct the actual value.
@@ -193,10 +241,13 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryInto;
 use std::hash::Hash;
+use std::path::Path;
 use std::time::SystemTime;
 
 use itertools::Itertools;
+use num_traits::FromPrimitive;
 
+use crate::error::ConversionError;
 use crate::types::ConcreteType;
 use crate::*;
 
@@ -273,10 +324,20 @@ impl DefaultCqlType for &str {
     type C = types::Text;
 }
 
+#[cfg(feature = "url")]
+impl DefaultCqlType for url::Url {
+    type C = types::Text;
+}
+
 impl DefaultCqlType for Vec<u8> {
     type C = types::Blob;
 }
 
+#[cfg(feature = "smallvec")]
+impl<const N: usize> DefaultCqlType for smallvec::SmallVec<[u8; N]> {
+    type C = types::Blob;
+}
+
 impl DefaultCqlType for proto::Decimal {
     type C = types::Decimal;
 }
@@ -352,7 +413,7 @@ where
     type C = types::Set<<T as DefaultCqlType>::C>;
 }
 
-impl<K, V> DefaultCqlType for HashMap<K, V>
+impl<K, V, S> DefaultCqlType for HashMap<K, V, S>
 where
     K: DefaultCqlType,
     V: DefaultCqlType,
@@ -380,6 +441,39 @@ pub trait IntoValue<C> {
     fn into_value(self) -> Value;
 }
 
+/// Object-safe variant of `Into<Value>`.
+///
+/// Allows to collect values of different Rust types into a single collection of
+/// `Box<dyn IntoValueAny>` and convert them to `Value`s later, e.g. when the bind values
+/// of a query are assembled at runtime.
+/// This trait is implemented for every type that can be converted into a `Value` with
+/// the default conversion.
+///
+/// # Example
+/// ```
+/// use stargate_grpc::Value;
+/// use stargate_grpc::into_value::IntoValueAny;
+///
+/// let values: Vec<Box<dyn IntoValueAny>> = vec![Box::new(1), Box::new("foo")];
+/// let values: Vec<Value> = values.into_iter().map(Value::from).collect();
+/// assert_eq!(values, vec![Value::bigint(1), Value::string("foo")]);
+/// ```
+pub trait IntoValueAny {
+    fn into_value(self: Box<Self>) -> Value;
+}
+
+impl<R: Into<Value>> IntoValueAny for R {
+    fn into_value(self: Box<Self>) -> Value {
+        (*self).into()
+    }
+}
+
+impl From<Box<dyn IntoValueAny>> for Value {
+    fn from(value: Box<dyn IntoValueAny>) -> Self {
+        value.into_value()
+    }
+}
+
 impl Value {
     /// Constructs a CQL boolean value without applying additional conversions.
     /// CQL type: `boolean`.
@@ -577,6 +671,19 @@ impl Value {
     }
 
     /// Creates a CQL `null` value.
+    ///
+    /// Nulls are untyped: the gRPC protocol has no way to attach a CQL type to a null,
+    /// and the server doesn't need one, because it knows the types of the bound columns.
+    /// If you want the compiler to check that a nullable value matches the column type,
+    /// pass `None` to [`Value::of_type`]; the result is the same untyped null:
+    ///
+    /// ```
+    /// use stargate_grpc::Value;
+    /// use stargate_grpc::types::Int;
+    ///
+    /// let value = Value::of_type(Int, None as Option<i32>);
+    /// assert_eq!(value, Value::null());
+    /// ```
     pub fn null() -> Value {
         Value {
             inner: Some(proto::value::Inner::Null(proto::value::Null {})),
@@ -634,6 +741,11 @@ impl Value {
         value.into_value()
     }
 
+    /// Constructs a CQL `tinyint` value.
+    pub fn tinyint(value: impl IntoValue<types::Tinyint>) -> Value {
+        value.into_value()
+    }
+
     /// Constructs a CQL `time` value.
     pub fn time(value: impl IntoValue<types::Time>) -> Value {
         value.into_value()
@@ -649,6 +761,28 @@ impl Value {
         value.into_value()
     }
 
+    /// Constructs a CQL `timeuuid` value.
+    ///
+    /// # Panics
+    /// Conversions to `timeuuid` panic if the UUID is not a version 1 UUID.
+    /// Use [`Value::try_timeuuid`] if the UUID may be of another version.
+    pub fn timeuuid(value: impl IntoValue<types::Timeuuid>) -> Value {
+        value.into_value()
+    }
+
+    /// Constructs a CQL `timeuuid` value, if the UUID is a version 1 UUID.
+    ///
+    /// Returns a `ConversionError` of `ConversionErrorKind::Incompatible` for UUIDs
+    /// of other versions, e.g. v4 or v7, which must be stored in a `uuid` column instead.
+    #[cfg(feature = "uuid")]
+    pub fn try_timeuuid(uuid: uuid::Uuid) -> Result<Value, ConversionError> {
+        if uuid.get_version_num() == 1 {
+            Ok(Value::raw_uuid(uuid.as_bytes()))
+        } else {
+            Err(ConversionError::incompatible::<_, types::Timeuuid>(uuid))
+        }
+    }
+
     /// Constructs a CQL `inet` value.
     pub fn inet(value: impl IntoValue<types::Inet>) -> Value {
         value.into_value()
@@ -659,6 +793,40 @@ impl Value {
         value.into_value()
     }
 
+    /// Constructs a CQL `blob` value from a string of hexadecimal digits,
+    /// e.g. given on the command line.
+    ///
+    /// The string may start with an optional `0x` prefix, so the output of `Display`
+    /// of a blob value can be parsed back. Both lowercase and uppercase digits are accepted.
+    ///
+    /// Returns a `ConversionError` of `ConversionErrorKind::Incompatible` if the string
+    /// contains characters that are not hexadecimal digits, or if it has an odd number of
+    /// digits, so that the last byte would be incomplete.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// let blob = Value::bytes_from_hex("0xcafe").unwrap();
+    /// assert_eq!(blob, Value::bytes(vec![0xca, 0xfe]));
+    /// assert_eq!(blob.to_string(), "0xcafe");
+    /// assert!(Value::bytes_from_hex("0xcaf").is_err());
+    /// ```
+    pub fn bytes_from_hex(hex: &str) -> Result<Value, ConversionError> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        let error = || ConversionError::incompatible::<_, types::Blob>(hex);
+        let digit = |c: u8| (c as char).to_digit(16).ok_or_else(error);
+        let bytes = digits
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| match pair {
+                [high, low] => Ok((digit(*high)? << 4 | digit(*low)?) as u8),
+                _ => Err(error()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Value::raw_bytes(bytes))
+    }
+
     /// Constructs a CQL `varint` value.
     pub fn varint(value: impl IntoValue<types::Varint>) -> Value {
         value.into_value()
@@ -669,11 +837,64 @@ impl Value {
         value.into_value()
     }
 
+    /// Constructs a CQL `decimal` value equal to `mantissa * 10^(-scale)`,
+    /// checking that the server can accept it.
+    ///
+    /// The `mantissa` must be the unscaled value encoded as a big-endian two's complement
+    /// integer, the same as the value of a `varint`, e.g. `[0x30, 0x39]` is 12345
+    /// and `[0xff]` is -1.
+    ///
+    /// Unlike [`Value::raw_decimal`], returns a `ConversionError`:
+    /// - of `ConversionErrorKind::Incompatible` if the mantissa is empty,
+    /// - of `ConversionErrorKind::OutOfRange` if the scale doesn't fit in the signed 32-bit integer
+    ///   Cassandra uses to store it.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// let price = Value::decimal_checked(2, &[0x30, 0x39]).unwrap();
+    /// assert_eq!(price.to_string(), "123.45");
+    /// assert!(Value::decimal_checked(2, &[]).is_err());
+    /// ```
+    pub fn decimal_checked(scale: u32, mantissa: &[u8]) -> Result<Value, ConversionError> {
+        if mantissa.is_empty() {
+            return Err(ConversionError::incompatible::<_, types::Decimal>(
+                "empty mantissa",
+            ));
+        }
+        if scale > i32::MAX as u32 {
+            return Err(ConversionError::out_of_range::<_, types::Decimal>(scale));
+        }
+        Ok(Value::raw_decimal(scale, mantissa.to_vec()))
+    }
+
     /// Constructs a CQL `ascii`, `varchar` or `text` value.
     pub fn string(value: impl IntoValue<types::Text>) -> Value {
         value.into_value()
     }
 
+    /// Constructs a CQL `varchar` or `text` value from a file system path.
+    ///
+    /// Returns a `ConversionError` of `ConversionErrorKind::Incompatible` if the path
+    /// is not valid UTF-8, which is possible on some platforms.
+    ///
+    /// # Example
+    /// ```
+    /// use std::path::Path;
+    /// use stargate_grpc::Value;
+    ///
+    /// let path = Value::try_path(Path::new("/var/lib/cassandra")).unwrap();
+    /// assert_eq!(path, Value::string("/var/lib/cassandra"));
+    /// ```
+    pub fn try_path(path: impl AsRef<Path>) -> Result<Value, ConversionError> {
+        let path = path.as_ref();
+        match path.to_str() {
+            Some(s) => Ok(Value::raw_string(s)),
+            None => Err(ConversionError::incompatible::<_, types::Text>(path)),
+        }
+    }
+
     /// Constructs a CQL `list` or `tuple` value.
     ///
     /// Items are converted to `Value` using the default conversion associated
@@ -697,6 +918,42 @@ impl Value {
         Value::list_of(types::Any, elements)
     }
 
+    /// Constructs a CQL `list<tuple<...>>` value from an iterator of Rust tuples.
+    ///
+    /// Such values can be bound to a multi-column `IN` restriction, e.g. to select rows
+    /// by a composite partition key. The clause
+    /// `WHERE (a, b) IN ((1, 'x'), (2, 'y'))` is written with a single bind marker
+    /// as `WHERE (a, b) IN ?`, and the bound value is a list with one tuple
+    /// per combination of the key columns.
+    ///
+    /// Tuple elements are converted using the default conversions associated with their types.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::{Query, Value};
+    ///
+    /// let keys = Value::list_of_tuples(vec![(1, "x"), (2, "y")]);
+    /// assert_eq!(
+    ///     keys,
+    ///     Value::list(vec![
+    ///         Value::list(vec![Value::bigint(1), Value::string("x")]),
+    ///         Value::list(vec![Value::bigint(2), Value::string("y")]),
+    ///     ])
+    /// );
+    ///
+    /// let query = Query::builder()
+    ///     .query("SELECT * FROM events WHERE (a, b) IN ?")
+    ///     .bind((keys,))
+    ///     .build();
+    /// ```
+    pub fn list_of_tuples<I, T>(tuples: I) -> Value
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoValue<types::List<types::Any>>,
+    {
+        Value::list_of(types::List(types::Any), tuples)
+    }
+
     /// Constructs a CQL `list`, `set` or `tuple` value.
     /// Allows to specify the target type of the elements.
     ///
@@ -717,6 +974,36 @@ impl Value {
         Value::raw_collection(elements)
     }
 
+    /// Constructs a CQL `list<bigint>` value from a slice of integers.
+    ///
+    /// Gives the same result as [`Value::list`], but skips the generic conversion
+    /// machinery and allocates the elements vector once, with the exact size.
+    /// Use it on hot paths writing large numeric lists.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// assert_eq!(Value::list_from_i64_slice(&[1, 2]), Value::list(vec![1, 2]));
+    /// ```
+    pub fn list_from_i64_slice(elements: &[i64]) -> Value {
+        Value::raw_collection(elements.iter().map(|&e| Value::raw_int(e)).collect())
+    }
+
+    /// Constructs a CQL `list<double>` value from a slice of floating point numbers.
+    ///
+    /// See [`Value::list_from_i64_slice`].
+    pub fn list_from_f64_slice(elements: &[f64]) -> Value {
+        Value::raw_collection(elements.iter().map(|&e| Value::raw_double(e)).collect())
+    }
+
+    /// Constructs a CQL `list<boolean>` value from a slice of booleans.
+    ///
+    /// See [`Value::list_from_i64_slice`].
+    pub fn list_from_bool_slice(elements: &[bool]) -> Value {
+        Value::raw_collection(elements.iter().map(|&e| Value::raw_boolean(e)).collect())
+    }
+
     /// Constructs a CQL `set` value.
     ///
     /// Actually it is just an alias for `list`,
@@ -740,6 +1027,65 @@ impl Value {
         Value::list_of(element_type, elements)
     }
 
+    /// Constructs a CQL `list` or `set` value from optional elements, skipping the `None`s.
+    ///
+    /// By default `None` is converted to [`Value::null`], also inside collections.
+    /// However, Cassandra doesn't allow null elements in lists, sets and maps
+    /// (both frozen and non-frozen), and the server rejects a query binding such a value.
+    /// Tuples and user defined types can contain nulls.
+    ///
+    /// Use this function if the missing elements can be left out, or
+    /// [`Value::list_without_nulls`] to report them as an error.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// let elements = vec![Some(1), None, Some(3)];
+    /// assert_eq!(Value::list_skip_nulls(elements), Value::list(vec![1, 3]));
+    /// ```
+    pub fn list_skip_nulls<I, T>(elements: I) -> Value
+    where
+        I: IntoIterator<Item = Option<T>>,
+        T: Into<Value>,
+    {
+        Value::list(elements.into_iter().flatten())
+    }
+
+    /// Constructs a CQL `list` or `set` value from optional elements,
+    /// failing if any of them is `None`.
+    ///
+    /// Returns a `ConversionError` of `ConversionErrorKind::Incompatible` pointing at the
+    /// position of the first `None` element.
+    /// See [`Value::list_skip_nulls`] for why null elements are not allowed in collections.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// assert_eq!(
+    ///     Value::list_without_nulls(vec![Some(1), Some(2)]).unwrap(),
+    ///     Value::list(vec![1, 2])
+    /// );
+    /// assert!(Value::list_without_nulls(vec![Some(1), None]).is_err());
+    /// ```
+    pub fn list_without_nulls<I, T>(elements: I) -> Result<Value, ConversionError>
+    where
+        I: IntoIterator<Item = Option<T>>,
+        T: Into<Value>,
+    {
+        let elements = elements
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| {
+                e.map(Into::into).ok_or_else(|| {
+                    ConversionError::incompatible::<_, Value>(format!("None at index {}", i))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Value::raw_collection(elements))
+    }
+
     /// Converts a collection of key-value pairs to a CQL `map` value.
     ///
     /// Keys and values of the map are converted to `Value` using the default conversions
@@ -779,6 +1125,42 @@ impl Value {
         Value::map_of(types::Any, types::Any, key_value_pairs)
     }
 
+    /// Constructs a CQL `map` value from separate slices of keys and values.
+    ///
+    /// The key at position `i` is paired with the value at the same position.
+    /// Keys and values are converted to `Value` using the default conversions
+    /// associated with their types.
+    ///
+    /// Returns a `ConversionError` of `ConversionErrorKind::WrongNumberOfItems`
+    /// if the slices differ in length.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// let keys = ["foo", "bar"];
+    /// let values = [1, 2];
+    /// assert_eq!(
+    ///     Value::map_from_slices(&keys, &values).unwrap(),
+    ///     Value::map(vec![("foo", 1), ("bar", 2)])
+    /// );
+    /// ```
+    pub fn map_from_slices<K, V>(keys: &[K], values: &[V]) -> Result<Value, ConversionError>
+    where
+        K: Clone + Into<Value>,
+        V: Clone + Into<Value>,
+    {
+        if keys.len() != values.len() {
+            let source = format!("{} keys and {} values", keys.len(), values.len());
+            return Err(ConversionError::wrong_number_of_items::<_, Value>(
+                source,
+                values.len(),
+                keys.len(),
+            ));
+        }
+        Ok(Value::map(keys.iter().cloned().zip(values.iter().cloned())))
+    }
+
     /// Converts a collection of key-value pairs to a CQL `map` value.
     /// Allows to specify the target key and value types.
     ///
@@ -858,6 +1240,36 @@ impl Value {
             .collect();
         Value::raw_udt(fields)
     }
+
+    /// Merges two user defined type values into a new one.
+    ///
+    /// The result contains all fields of `base` and `overlay`.
+    /// Fields present in both values take their values from `overlay`.
+    /// Useful for read-modify-write updates of a few fields of a UDT value read from the
+    /// database.
+    ///
+    /// Returns an error if any of the arguments is not a UDT value.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Value;
+    ///
+    /// let base = Value::udt(vec![("street", "Main St"), ("city", "Springfield")]);
+    /// let overlay = Value::udt(vec![("street", "Elm St")]);
+    /// let merged = Value::merge_udt(base, overlay).unwrap();
+    /// assert_eq!(merged, Value::udt(vec![("street", "Elm St"), ("city", "Springfield")]));
+    /// ```
+    pub fn merge_udt(base: Value, overlay: Value) -> Result<Value, ConversionError> {
+        match (base.inner, overlay.inner) {
+            (Some(proto::value::Inner::Udt(mut base)), Some(proto::value::Inner::Udt(overlay))) => {
+                base.fields.extend(overlay.fields);
+                Ok(Value::raw_udt(base.fields))
+            }
+            (Some(proto::value::Inner::Udt(_)), other) | (other, _) => {
+                Err(ConversionError::incompatible::<_, proto::UdtValue>(other))
+            }
+        }
+    }
 }
 
 impl<R> From<R> for Value
@@ -931,10 +1343,21 @@ gen_conversion!(f64 => types::Double; x => Value::raw_double(x));
 
 gen_conversion!(String => types::Text; x => Value::raw_string(x));
 gen_conversion!(&str => types::Text; x => Value::raw_string(x.to_string()));
+#[cfg(feature = "url")]
+gen_conversion!(url::Url => types::Text; x => Value::raw_string(x));
 
 gen_conversion!(Vec<u8> => types::Blob; x => Value::raw_bytes(x));
 gen_conversion!(Vec<u8> => types::Varint; x => Value::raw_varint(x));
 
+/// Blobs are always stored in a `Vec<u8>` in a `Value`.
+/// Converting a blob that has spilled to the heap reuses its buffer.
+#[cfg(feature = "smallvec")]
+impl<const N: usize> IntoValue<types::Blob> for smallvec::SmallVec<[u8; N]> {
+    fn into_value(self) -> Value {
+        Value::raw_bytes(self.into_vec())
+    }
+}
+
 gen_conversion!([u8; 4] => types::Inet; x => Value::raw_inet(x.to_vec()));
 gen_conversion!(&[u8; 4] => types::Inet; x => Value::raw_inet(x.to_vec()));
 gen_conversion!([u8; 16] => types::Inet; x => Value::raw_inet(x.to_vec()));
@@ -949,11 +1372,33 @@ gen_conversion!(proto::Uuid => types::Uuid; x =>
     Value::raw_uuid(&x.value.try_into().expect("16 bytes")));
 gen_conversion!(proto::Varint => types::Varint; x => Value::raw_varint(x.value));
 
-gen_conversion!(SystemTime => types::Timestamp; x =>
-    Value::raw_int(x.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as i64));
+gen_conversion!(SystemTime => types::Timestamp; x => Value::raw_int(unix_millis(x)));
+
+/// Returns the signed number of milliseconds since the Unix epoch, rounded down
+/// and clamped to the range of `i64`.
+fn unix_millis(time: SystemTime) -> i64 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(elapsed) => i64::from_u128(elapsed.as_millis()).unwrap_or(i64::MAX),
+        Err(e) => {
+            let millis = e.duration().as_nanos().div_ceil(1_000_000);
+            i64::from_u128(millis).map_or(i64::MIN, |millis| -millis)
+        }
+    }
+}
 
 #[cfg(feature = "uuid")]
 gen_conversion!(uuid::Uuid => types::Uuid; x => Value::raw_uuid(x.as_bytes()));
+#[cfg(feature = "uuid")]
+gen_conversion!(uuid::Uuid => types::Timeuuid; x => {
+    Value::try_timeuuid(x).unwrap_or_else(|_| {
+        panic!(
+            "Cannot convert UUID {} of version {} to timeuuid; \
+            timeuuid requires a version 1 UUID, use a uuid column instead",
+            x,
+            x.get_version_num()
+        )
+    })
+});
 
 /// Generates generic conversion from a Rust tuple to `Value`.
 ///
@@ -1074,6 +1519,9 @@ where
     }
 }
 
+/// A `Vec<Value>` converted to `types::List<types::Any>` doesn't need a dedicated impl:
+/// converting a `Value` to `types::Any` returns it unchanged, so the elements are moved
+/// into the list as they are.
 impl<R, C> IntoValue<types::List<C>> for Vec<R>
 where
     R: IntoValue<C>,
@@ -1143,7 +1591,7 @@ where
     }
 }
 
-impl<RK, RV, CK, CV> IntoValue<types::Map<CK, CV>> for HashMap<RK, RV>
+impl<RK, RV, CK, CV, S> IntoValue<types::Map<CK, CV>> for HashMap<RK, RV, S>
 where
     RK: IntoValue<CK> + Eq + Hash,
     RV: IntoValue<CV>,
@@ -1156,6 +1604,26 @@ where
     }
 }
 
+impl<K, V> IntoValue<types::Udt> for BTreeMap<K, V>
+where
+    K: ToString,
+    V: Into<Value>,
+{
+    fn into_value(self) -> Value {
+        Value::udt(self)
+    }
+}
+
+impl<K, V, S> IntoValue<types::Udt> for HashMap<K, V, S>
+where
+    K: ToString,
+    V: Into<Value>,
+{
+    fn into_value(self) -> Value {
+        Value::udt(self)
+    }
+}
+
 #[cfg(feature = "chrono")]
 impl<Tz: chrono::TimeZone> IntoValue<types::Timestamp> for chrono::DateTime<Tz> {
     fn into_value(self) -> Value {
@@ -1176,12 +1644,15 @@ impl<Tz: chrono::TimeZone> IntoValue<types::Date> for chrono::Date<Tz> {
 #[cfg(test)]
 mod test {
     use itertools::Itertools;
+    use std::collections::hash_map::DefaultHasher;
     use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::hash::BuildHasherDefault;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
     use proto::value::Inner;
 
-    use crate::types::{Any, Bigint, Date, List, Map, Time};
+    use crate::error::ConversionErrorKind;
+    use crate::types::{Any, Bigint, Date, Int, List, Map, Time};
     use crate::*;
 
     #[test]
@@ -1229,6 +1700,51 @@ mod test {
         assert_eq!(v, Value::string("foo"));
     }
 
+    #[test]
+    fn convert_path_into_value() {
+        use std::path::{Path, PathBuf};
+
+        let v = Value::try_path(Path::new("/var/lib/cassandra")).unwrap();
+        assert_eq!(v, Value::string("/var/lib/cassandra"));
+
+        let v = Value::try_path(PathBuf::from("data/users.csv")).unwrap();
+        assert_eq!(v, Value::string("data/users.csv"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn reject_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::path::Path;
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/\xff.txt"));
+        let error = Value::try_path(path).unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::Incompatible);
+    }
+
+    #[test]
+    fn construct_large_lists_from_primitive_slices() {
+        let ints: Vec<i64> = (0..100_000).collect();
+        let list = Value::list_from_i64_slice(&ints);
+        assert_eq!(list, Value::list(ints.clone()));
+        let read: Vec<i64> = list.try_into().unwrap();
+        assert_eq!(read, ints);
+
+        let doubles: Vec<f64> = ints.iter().map(|&i| i as f64 / 2.0).collect();
+        let list = Value::list_from_f64_slice(&doubles);
+        assert_eq!(list, Value::list(doubles.clone()));
+
+        let bools: Vec<bool> = ints.iter().map(|&i| i % 3 == 0).collect();
+        let list = Value::list_from_bool_slice(&bools);
+        assert_eq!(list, Value::list(bools.clone()));
+
+        assert_eq!(
+            Value::list_from_i64_slice(&[]),
+            Value::list(Vec::<i64>::new())
+        );
+    }
+
     #[test]
     fn convert_vector_into_bytes_value() {
         let buf: Vec<u8> = vec![1, 2];
@@ -1236,6 +1752,36 @@ mod test {
         assert_eq!(v, Value::bytes(vec![1, 2]))
     }
 
+    #[test]
+    fn convert_hex_string_into_bytes_value() {
+        let v = Value::bytes_from_hex("00ff7fAB").unwrap();
+        assert_eq!(v, Value::bytes(vec![0x00, 0xff, 0x7f, 0xab]));
+        assert_eq!(Value::bytes_from_hex(&v.to_string()).unwrap(), v);
+        assert_eq!(Value::bytes_from_hex("0x").unwrap(), Value::bytes(vec![]));
+
+        let odd_length = Value::bytes_from_hex("0x123").unwrap_err();
+        assert_eq!(odd_length.kind, ConversionErrorKind::Incompatible);
+        let invalid_char = Value::bytes_from_hex("0x12zz").unwrap_err();
+        assert_eq!(invalid_char.kind, ConversionErrorKind::Incompatible);
+        let sign = Value::bytes_from_hex("+1").unwrap_err();
+        assert_eq!(sign.kind, ConversionErrorKind::Incompatible);
+        let non_ascii = Value::bytes_from_hex("é1").unwrap_err();
+        assert_eq!(non_ascii.kind, ConversionErrorKind::Incompatible);
+    }
+
+    #[test]
+    fn construct_checked_decimal() {
+        let v = Value::decimal_checked(3, &[0xff, 0x85]).unwrap();
+        assert_eq!(v, Value::raw_decimal(3, vec![0xff, 0x85]));
+        let v = Value::decimal_checked(i32::MAX as u32, &[0x01]).unwrap();
+        assert_eq!(v, Value::raw_decimal(i32::MAX as u32, vec![0x01]));
+
+        let empty = Value::decimal_checked(0, &[]).unwrap_err();
+        assert_eq!(empty.kind, ConversionErrorKind::Incompatible);
+        let large_scale = Value::decimal_checked(i32::MAX as u32 + 1, &[0x01]).unwrap_err();
+        assert_eq!(large_scale.kind, ConversionErrorKind::OutOfRange);
+    }
+
     #[test]
     fn convert_uuid_into_value() {
         let uuid = proto::Uuid { value: vec![1; 16] };
@@ -1252,6 +1798,52 @@ mod test {
         assert_eq!(v1, v2)
     }
 
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn convert_v1_uuid_into_timeuuid_value() {
+        let uuid = uuid::Uuid::parse_str("c232ab00-9414-11ec-b3c8-9f6bdeced846").unwrap();
+        let v = Value::of_type(types::Timeuuid, uuid);
+        assert_eq!(v, Value::uuid(uuid))
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn convert_any_uuid_version_into_uuid_value() {
+        let v4 = uuid::Uuid::parse_str("936da01f-9abd-4d9d-80c7-02af85c822a8").unwrap();
+        let v7 = uuid::Uuid::parse_str("017f22e2-79b0-7cc3-98c4-dc0c0c07398f").unwrap();
+        assert_eq!(Value::uuid(v4), Value::raw_uuid(v4.as_bytes()));
+        assert_eq!(Value::uuid(v7), Value::raw_uuid(v7.as_bytes()));
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    #[should_panic(expected = "timeuuid requires a version 1 UUID")]
+    fn convert_v4_uuid_into_timeuuid_value() {
+        let uuid = uuid::Uuid::parse_str("936da01f-9abd-4d9d-80c7-02af85c822a8").unwrap();
+        Value::timeuuid(uuid);
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    #[should_panic(expected = "timeuuid requires a version 1 UUID")]
+    fn convert_v7_uuid_into_timeuuid_value() {
+        let uuid = uuid::Uuid::parse_str("017f22e2-79b0-7cc3-98c4-dc0c0c07398f").unwrap();
+        Value::timeuuid(uuid);
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn try_convert_uuid_into_timeuuid_value() {
+        let v1 = uuid::Uuid::parse_str("c232ab00-9414-11ec-b3c8-9f6bdeced846").unwrap();
+        let v4 = uuid::Uuid::parse_str("936da01f-9abd-4d9d-80c7-02af85c822a8").unwrap();
+        let v7 = uuid::Uuid::parse_str("017f22e2-79b0-7cc3-98c4-dc0c0c07398f").unwrap();
+        assert_eq!(Value::try_timeuuid(v1).unwrap(), Value::uuid(v1));
+        for uuid in [v4, v7] {
+            let error = Value::try_timeuuid(uuid).unwrap_err();
+            assert_eq!(error.kind, ConversionErrorKind::Incompatible);
+        }
+    }
+
     #[test]
     fn convert_inet_into_value() {
         let inet = proto::Inet {
@@ -1271,6 +1863,59 @@ mod test {
         assert_eq!(v, Value::raw_decimal(2, vec![10, 0]))
     }
 
+    #[test]
+    fn convert_list_of_decimals_round_trip() {
+        let decimals = vec![
+            proto::Decimal {
+                scale: 2,
+                value: vec![10, 0],
+            },
+            proto::Decimal {
+                scale: 0,
+                value: vec![0xff],
+            },
+        ];
+        let v = Value::from(decimals.clone());
+        assert_eq!(
+            v,
+            Value::list(vec![
+                Value::raw_decimal(2, vec![10, 0]),
+                Value::raw_decimal(0, vec![0xff])
+            ])
+        );
+        let result: Vec<proto::Decimal> = v.try_into().unwrap();
+        assert_eq!(result, decimals);
+    }
+
+    #[test]
+    fn convert_map_of_decimals_round_trip() {
+        let mut prices = BTreeMap::new();
+        prices.insert(
+            "apple".to_string(),
+            proto::Decimal {
+                scale: 2,
+                value: vec![0x01, 0x2c],
+            },
+        );
+        prices.insert(
+            "pear".to_string(),
+            proto::Decimal {
+                scale: 1,
+                value: vec![0x19],
+            },
+        );
+        let v = Value::of_type(Map(types::Text, types::Decimal), prices.clone());
+        assert_eq!(
+            v,
+            Value::map(vec![
+                ("apple", Value::raw_decimal(2, vec![0x01, 0x2c])),
+                ("pear", Value::raw_decimal(1, vec![0x19]))
+            ])
+        );
+        let result: BTreeMap<String, proto::Decimal> = v.try_into().unwrap();
+        assert_eq!(result, prices);
+    }
+
     #[test]
     fn convert_varint_into_value() {
         let varint = proto::Varint { value: vec![10, 0] };
@@ -1288,6 +1933,20 @@ mod test {
         assert_eq!(value2, Value::bigint(unix_time));
     }
 
+    #[test]
+    fn convert_system_time_before_epoch_into_value() {
+        let time = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(Value::from(time), Value::bigint(-1500));
+        let time = UNIX_EPOCH - Duration::from_micros(1500);
+        assert_eq!(Value::from(time), Value::bigint(-2));
+        let time = UNIX_EPOCH + Duration::from_micros(1500);
+        assert_eq!(Value::from(time), Value::bigint(1));
+
+        let time = UNIX_EPOCH - Duration::from_secs(86400 * 365);
+        let read: SystemTime = Value::from(time).try_into().unwrap();
+        assert_eq!(read, time);
+    }
+
     #[test]
     #[cfg(feature = "chrono")]
     fn convert_chrono_utc_time_into_value() {
@@ -1326,6 +1985,63 @@ mod test {
         assert_eq!(value, Value::date(0));
     }
 
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn convert_list_of_chrono_timestamps_round_trip() {
+        use chrono::{DateTime, Utc};
+        use std::time::Duration;
+        let timestamp = |millis| DateTime::<Utc>::from(UNIX_EPOCH + Duration::from_millis(millis));
+        let timestamps = vec![timestamp(0), timestamp(1633478400021)];
+        let value = Value::from(timestamps.clone());
+        assert_eq!(
+            value,
+            Value::list(vec![
+                Value::timestamp(0),
+                Value::timestamp(1633478400021_i64)
+            ])
+        );
+        let read: Vec<DateTime<Utc>> = value.try_into().unwrap();
+        assert_eq!(read, timestamps);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn convert_map_of_chrono_dates_round_trip() {
+        use chrono::{Date, DateTime, Utc};
+        use std::time::Duration;
+        let day =
+            |n: u64| DateTime::<Utc>::from(UNIX_EPOCH + Duration::from_secs(n * 86400)).date();
+        let mut map = BTreeMap::new();
+        map.insert(day(0), "epoch".to_string());
+        map.insert(day(2), "two days later".to_string());
+        let value = Value::from(map.clone());
+        assert_eq!(
+            value,
+            Value::map(vec![
+                (Value::date(0), Value::string("epoch")),
+                (Value::date(2), Value::string("two days later")),
+            ])
+        );
+        let read: BTreeMap<Date<Utc>, String> = value.clone().try_into().unwrap();
+        assert_eq!(read, map);
+        let read: HashMap<Date<Utc>, String> = value.try_into().unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[&day(2)], "two days later");
+    }
+
+    #[test]
+    fn convert_list_of_u64_into_list_of_time() {
+        // u64 has no default CQL type, so the target type must be given explicitly
+        let times = vec![0_u64, 43_200_000_000_000];
+        let value = Value::of_type(List(Time), times.clone());
+        assert_eq!(
+            value,
+            Value::list(vec![Value::time(0), Value::time(43_200_000_000_000_u64)])
+        );
+        let read: Vec<u64> = value.try_into().unwrap();
+        assert_eq!(read, times);
+    }
+
     #[test]
     fn convert_tuple_into_default_value() {
         let tuple = (1, "foo");
@@ -1333,6 +2049,56 @@ mod test {
         assert_eq!(v, Value::list(vec![Value::bigint(1), Value::string("foo")]))
     }
 
+    #[test]
+    fn convert_optional_elements_into_list_skipping_nulls() {
+        let elements = vec![None, Some("a"), None, Some("b")];
+        let value = Value::list_skip_nulls(elements);
+        assert_eq!(value, Value::list(vec!["a", "b"]));
+
+        let value = Value::list_skip_nulls(Vec::<Option<i64>>::new());
+        assert_eq!(value, Value::list(Vec::<i64>::new()));
+    }
+
+    #[test]
+    fn convert_optional_elements_into_list_without_nulls() {
+        let value = Value::list_without_nulls(vec![Some(1), Some(2)]).unwrap();
+        assert_eq!(value, Value::list(vec![1, 2]));
+
+        let error = Value::list_without_nulls(vec![Some(1), None, None]).unwrap_err();
+        assert_eq!(error.kind, ConversionErrorKind::Incompatible);
+        assert!(format!("{}", error).contains("None at index 1"));
+    }
+
+    #[test]
+    fn convert_tuples_into_list_of_tuples_value() {
+        let v = Value::list_of_tuples(vec![(1, "x"), (2, "y")]);
+        let tuple = |a, b| Value {
+            inner: Some(Inner::Collection(proto::Collection {
+                elements: vec![Value::bigint(a), Value::string(b)],
+            })),
+        };
+        assert_eq!(
+            v,
+            Value {
+                inner: Some(Inner::Collection(proto::Collection {
+                    elements: vec![tuple(1, "x"), tuple(2, "y")]
+                }))
+            }
+        );
+    }
+
+    #[test]
+    fn convert_tuple_of_options_into_value() {
+        let timestamp = UNIX_EPOCH + std::time::Duration::from_millis(1000);
+        let tuple = (Some(1), None::<String>, Some(timestamp));
+        let expected = Value::list(vec![Value::bigint(1), Value::null(), Value::raw_int(1000)]);
+        assert_eq!(Value::from(tuple.clone()), expected);
+        assert_eq!(
+            Value::of_type((Bigint, types::Text, types::Timestamp), tuple),
+            expected
+        );
+    }
+
     #[test]
     fn convert_single_item_tuple_into_value() {
         let tuple = (1,);
@@ -1384,6 +2150,39 @@ mod test {
         assert_eq!(v, Value::null());
     }
 
+    #[test]
+    fn bind_boxed_dynamic_values() {
+        use crate::into_value::IntoValueAny;
+        let values: Vec<Box<dyn IntoValueAny>> = vec![
+            Box::new(1),
+            Box::new("foo"),
+            Box::new(Some(2.0)),
+            Box::new(vec![true]),
+            Box::new(Value::null()),
+        ];
+        let query = Query::builder()
+            .query("INSERT INTO t(a, b, c, d, e) VALUES (?, ?, ?, ?, ?)")
+            .bind(values.into_iter().map(Value::from).collect::<Vec<_>>())
+            .build();
+        assert_eq!(
+            query.values.unwrap().values,
+            vec![
+                Value::bigint(1),
+                Value::string("foo"),
+                Value::double(2.0),
+                Value::list(vec![Value::boolean(true)]),
+                Value::null(),
+            ]
+        );
+    }
+
+    #[test]
+    fn null_carries_no_type() {
+        let v: Value = Value::of_type(Int, None as Option<i32>);
+        assert_eq!(v.inner, Some(Inner::Null(proto::value::Null {})));
+        assert_eq!(v, Value::null());
+    }
+
     #[test]
     fn convert_vec_of_i64_into_value() {
         let list = vec![1, 2];
@@ -1393,6 +2192,25 @@ mod test {
         assert_eq!(v1, v2);
     }
 
+    #[test]
+    fn convert_vec_of_values_into_list_value() {
+        let udt = Value::udt(vec![
+            ("id", Value::bigint(1)),
+            ("tags", Value::list(vec!["a"])),
+        ]);
+        let values = vec![
+            Value::bigint(1),
+            Value::string("foo"),
+            Value::null(),
+            Value::list(vec![Value::double(1.5), Value::unset()]),
+            udt,
+        ];
+        let expected = Value::raw_collection(values.clone());
+        assert_eq!(Value::list(values.clone()), expected);
+        assert_eq!(Value::of_type(List(Any), values.clone()), expected);
+        assert_eq!(expected.as_collection().unwrap(), values.as_slice());
+    }
+
     #[test]
     fn convert_nested_vec_i64_into_value() {
         let list = vec![vec![1, 2]];
@@ -1480,6 +2298,17 @@ mod test {
         );
     }
 
+    #[test]
+    fn convert_hash_map_with_custom_hasher_into_value() {
+        let mut map: HashMap<_, _, BuildHasherDefault<DefaultHasher>> = HashMap::default();
+        map.insert(1, "foo");
+        assert_eq!(Value::from(map.clone()), Value::map(vec![(1, "foo")]));
+        assert_eq!(
+            Value::of_type(types::Udt, map),
+            Value::udt(vec![("1", "foo")])
+        );
+    }
+
     #[test]
     fn convert_hash_map_to_udt_value() {
         let mut map = HashMap::new();
@@ -1504,4 +2333,89 @@ mod test {
             inner => assert!(false, "Unexpected udt inner value {:?}", inner),
         }
     }
+
+    #[test]
+    fn convert_slices_into_map_value() {
+        let keys = [1, 2];
+        let values = ["foo", "bar"];
+        let v = Value::map_from_slices(&keys, &values).unwrap();
+        assert_eq!(
+            v,
+            Value::map(vec![
+                (Value::bigint(1), Value::string("foo")),
+                (Value::bigint(2), Value::string("bar"))
+            ])
+        );
+    }
+
+    #[test]
+    fn convert_slices_of_different_length_into_map_value() {
+        let keys = [1, 2, 3];
+        let values = ["foo", "bar"];
+        let err = Value::map_from_slices(&keys, &values).unwrap_err();
+        assert_eq!(
+            err.kind,
+            ConversionErrorKind::WrongNumberOfItems {
+                actual: 2,
+                expected: 3
+            }
+        );
+    }
+
+    #[test]
+    fn convert_hash_map_into_udt_value() {
+        let mut fields = HashMap::new();
+        fields.insert("id", Value::bigint(1));
+        fields.insert("login", Value::string("user"));
+        let v = Value::of_type(types::Udt, fields);
+        match v.inner {
+            Some(Inner::Udt(udt)) => {
+                assert_eq!(udt.fields.len(), 2);
+                assert_eq!(udt.fields["id"], Value::bigint(1));
+                assert_eq!(udt.fields["login"], Value::string("user"));
+            }
+            other => panic!("Unexpected value {:?}", other),
+        }
+    }
+
+    #[test]
+    fn convert_btree_map_into_udt_value() {
+        let mut fields = BTreeMap::new();
+        fields.insert("id".to_string(), 1);
+        let v = Value::of_type(types::Udt, fields.clone());
+        assert_eq!(v, Value::udt(fields.clone()));
+        assert_ne!(v, Value::from(fields));
+    }
+
+    #[test]
+    fn merge_udt_values() {
+        let base = Value::udt(vec![
+            ("id", Value::bigint(1)),
+            ("login", Value::string("user")),
+            ("email", Value::string("user@example.com")),
+        ]);
+        let overlay = Value::udt(vec![
+            ("login", Value::string("new_user")),
+            ("active", Value::boolean(true)),
+        ]);
+        let merged = Value::merge_udt(base, overlay).unwrap();
+        assert_eq!(
+            merged,
+            Value::udt(vec![
+                ("id", Value::bigint(1)),
+                ("login", Value::string("new_user")),
+                ("email", Value::string("user@example.com")),
+                ("active", Value::boolean(true)),
+            ])
+        );
+    }
+
+    #[test]
+    fn merge_udt_rejects_non_udt_values() {
+        let udt = Value::udt(vec![("id", 1)]);
+        let result = Value::merge_udt(udt.clone(), Value::bigint(1));
+        assert_eq!(result.unwrap_err().kind, ConversionErrorKind::Incompatible);
+        let result = Value::merge_udt(Value::null(), udt);
+        assert_eq!(result.unwrap_err().kind, ConversionErrorKind::Incompatible);
+    }
 }
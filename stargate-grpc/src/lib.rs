@@ -270,8 +270,14 @@ pub use proto::{Batch, Consistency, Query, ResultSet, Row, Value};
 pub use stargate_grpc_derive::*;
 
 pub mod client;
+pub mod compare;
+#[cfg(feature = "polars")]
+pub mod dataframe;
+mod display;
 pub mod from_value;
+pub mod hash;
 pub mod into_value;
+pub mod paging;
 pub mod query;
 pub mod result;
 
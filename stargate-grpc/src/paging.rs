@@ -0,0 +1,328 @@
+//! Automatic paging through large result sets.
+//!
+//! A query that returns many rows is executed in pages. Each [`ResultSet`] page comes
+//! back with a paging state that must be passed in the next query to resume iteration.
+//! [`PagingStream`] does this for you and exposes all the rows as a single
+//! asynchronous stream.
+//!
+//! ## Backpressure
+//! Pages are fetched on demand. By default, the next page is requested only after all the rows
+//! of the current page have been consumed and the stream is polled for more.
+//! Hence, a slow consumer never causes pages to pile up in memory.
+//!
+//! Waiting for each page only when it is needed adds latency. You can trade some memory
+//! for throughput by setting the [prefetch depth](PagingStream::prefetch). The stream then keeps
+//! requesting pages ahead of the consumer, until that many pages are buffered in addition to
+//! the page currently being consumed. Pages are always fetched one at a time, because each
+//! request needs the paging state returned with the previous page.
+//! Fetching progresses only while the stream is being polled.
+//!
+//! # Example
+//! ```no_run
+//! use std::convert::TryInto;
+//! use futures_util::StreamExt;
+//! use stargate_grpc::{Query, StargateClient};
+//!
+//! # async fn scan(client: &StargateClient) -> anyhow::Result<()> {
+//! let query = Query::builder()
+//!     .keyspace("ks")
+//!     .query("SELECT id, login FROM users")
+//!     .page_size(1000)
+//!     .build();
+//!
+//! let mut rows = client.paging_stream(query).prefetch(1);
+//! while let Some(row) = rows.next().await {
+//!     let (id, login): (i64, String) = row?.try_into()?;
+//!     // ...
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::error::{ConversionError, QueryError};
+use crate::proto::{Query, QueryParameters, ResultSet, Row};
+use crate::StargateClient;
+
+type PageFuture = Pin<Box<dyn Future<Output = Result<ResultSet, QueryError>> + Send>>;
+type PageFetcher = Box<dyn FnMut(Query) -> PageFuture + Send>;
+
+/// Streams rows of all pages returned by a query.
+///
+/// Call [`StargateClient::paging_stream`] to obtain one.
+/// See the [module documentation](self) for the details on when the pages are fetched.
+pub struct PagingStream {
+    fetch: PageFetcher,
+    query: Query,
+    prefetch: usize,
+    rows: std::vec::IntoIter<Row>,
+    pages: VecDeque<ResultSet>,
+    in_flight: Option<PageFuture>,
+    done: bool,
+}
+
+impl PagingStream {
+    /// Creates a stream that obtains the pages by calling `fetch`.
+    pub(crate) fn new(query: Query, fetch: PageFetcher) -> PagingStream {
+        PagingStream {
+            fetch,
+            query,
+            prefetch: 0,
+            rows: Vec::new().into_iter(),
+            pages: VecDeque::new(),
+            in_flight: None,
+            done: false,
+        }
+    }
+
+    /// Sets the maximum number of pages fetched ahead of the page currently being consumed.
+    ///
+    /// Default is 0, which means the next page is requested only after the rows of the
+    /// current page have been consumed.
+    pub fn prefetch(mut self, pages: usize) -> Self {
+        self.prefetch = pages;
+        self
+    }
+
+    /// Moves to the next buffered page if all rows of the current page have been consumed.
+    fn advance(&mut self) {
+        while self.rows.len() == 0 {
+            match self.pages.pop_front() {
+                Some(page) => self.rows = page.rows.into_iter(),
+                None => break,
+            }
+        }
+    }
+
+    /// Returns true if another page should be requested now.
+    fn wants_next_page(&self) -> bool {
+        !self.done
+            && self.in_flight.is_none()
+            && (self.rows.len() == 0 || self.pages.len() < self.prefetch)
+    }
+
+    fn start_fetch(&mut self) {
+        let query = self.query.clone();
+        self.in_flight = Some((self.fetch)(query));
+    }
+
+    /// Buffers the received page and remembers where to continue.
+    fn accept(&mut self, mut page: ResultSet) {
+        match page.take_paging_state() {
+            Some(paging_state) => {
+                self.query
+                    .parameters
+                    .get_or_insert_with(QueryParameters::default)
+                    .paging_state = Some(paging_state)
+            }
+            None => self.done = true,
+        }
+        self.pages.push_back(page);
+    }
+}
+
+impl Stream for PagingStream {
+    type Item = Result<Row, QueryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            this.advance();
+            if this.wants_next_page() {
+                this.start_fetch();
+            }
+            if let Some(page) = this.in_flight.as_mut() {
+                if let Poll::Ready(result) = page.as_mut().poll(cx) {
+                    this.in_flight = None;
+                    match result {
+                        Ok(page) => this.accept(page),
+                        Err(e) => {
+                            this.done = true;
+                            return Poll::Ready(Some(Err(e)));
+                        }
+                    }
+                    continue;
+                }
+            }
+            return match this.rows.next() {
+                Some(row) => Poll::Ready(Some(Ok(row))),
+                None if this.in_flight.is_some() => Poll::Pending,
+                None => Poll::Ready(None),
+            };
+        }
+    }
+}
+
+impl StargateClient {
+    /// Executes the query and returns the rows of a single page converted to `T`,
+    /// together with the paging state needed to fetch the next page.
+    ///
+    /// The paging state is `None` if this is the last page.
+    /// Pass it to [`QueryBuilder::paging_state`](crate::query::QueryBuilder::paging_state)
+    /// to fetch the next page. This is handy for exposing cursor-based pagination
+    /// in your own APIs, where each call returns one page and a cursor.
+    ///
+    /// # Errors
+    /// Returns [`QueryError::Status`] if the query fails,
+    /// or [`QueryError::Conversion`] if the response doesn't contain a result set or
+    /// any of the rows can't be converted to `T`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use stargate_grpc::{Query, StargateClient};
+    ///
+    /// # async fn list_users(mut client: StargateClient, cursor: Option<Vec<u8>>)
+    /// #     -> anyhow::Result<()> {
+    /// let mut builder = Query::builder()
+    ///     .keyspace("ks")
+    ///     .query("SELECT id, login FROM users")
+    ///     .page_size(100);
+    /// if let Some(cursor) = cursor {
+    ///     builder = builder.paging_state(cursor);
+    /// }
+    /// let (users, next_cursor): (Vec<(i64, String)>, _) =
+    ///     client.query_page(builder.build()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_page<T>(
+        &mut self,
+        query: Query,
+    ) -> Result<(Vec<T>, Option<Vec<u8>>), QueryError>
+    where
+        T: TryFrom<Row, Error = ConversionError>,
+    {
+        let response = self.execute_query(query).await?;
+        let mut result_set = ResultSet::try_from(response)?;
+        let paging_state = result_set.take_paging_state();
+        let rows = result_set
+            .rows
+            .into_iter()
+            .map(T::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((rows, paging_state))
+    }
+
+    /// Executes the query and returns a stream of rows from all the pages of the result.
+    ///
+    /// Pages are fetched automatically as the stream is consumed.
+    /// Use [`QueryBuilder::page_size`](crate::query::QueryBuilder::page_size)
+    /// to control the number of rows in each page.
+    pub fn paging_stream(&self, query: Query) -> PagingStream {
+        let client = self.clone();
+        PagingStream::new(
+            query,
+            Box::new(move |query| {
+                let mut client = client.clone();
+                Box::pin(async move {
+                    let response = client.execute_query(query).await?;
+                    Ok(ResultSet::try_from(response)?)
+                })
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::Value;
+
+    /// Returns a stream over `page_count` pages, each holding 2 rows,
+    /// and a counter of the pages fetched so far.
+    fn counting_stream(page_count: u8) -> (PagingStream, Arc<AtomicUsize>) {
+        let fetched = Arc::new(AtomicUsize::new(0));
+        let counter = fetched.clone();
+        let fetch: PageFetcher = Box::new(move |query: Query| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let page = query
+                .parameters
+                .and_then(|p| p.paging_state)
+                .map(|s| s[0])
+                .unwrap_or(0);
+            let next_page = page + 1;
+            let result_set = ResultSet {
+                columns: vec![],
+                rows: vec![
+                    Row {
+                        values: vec![Value::bigint(page as i64 * 2)],
+                    },
+                    Row {
+                        values: vec![Value::bigint(page as i64 * 2 + 1)],
+                    },
+                ],
+                paging_state: if next_page < page_count {
+                    Some(vec![next_page])
+                } else {
+                    None
+                },
+            };
+            Box::pin(async move { Ok(result_set) })
+        });
+        let query = Query::builder().query("SELECT id FROM table").build();
+        (PagingStream::new(query, fetch), fetched)
+    }
+
+    fn next_id(stream: &mut PagingStream) -> Option<i64> {
+        futures_executor::block_on(stream.next()).map(|row| row.unwrap().try_get(0).unwrap())
+    }
+
+    #[test]
+    fn stream_rows_of_all_pages() {
+        let (stream, fetched) = counting_stream(3);
+        let rows: Vec<_> = futures_executor::block_on(stream.collect::<Vec<_>>());
+        let ids: Vec<i64> = rows
+            .into_iter()
+            .map(|row| row.unwrap().try_get(0).unwrap())
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(fetched.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn fetch_next_page_only_after_current_page_is_drained() {
+        let (mut stream, fetched) = counting_stream(3);
+        assert_eq!(fetched.load(Ordering::SeqCst), 0);
+        assert_eq!(next_id(&mut stream), Some(0));
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+        assert_eq!(next_id(&mut stream), Some(1));
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+        assert_eq!(next_id(&mut stream), Some(2));
+        assert_eq!(fetched.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn prefetch_pages_ahead() {
+        let (stream, fetched) = counting_stream(4);
+        let mut stream = stream.prefetch(1);
+        assert_eq!(next_id(&mut stream), Some(0));
+        assert_eq!(fetched.load(Ordering::SeqCst), 2);
+        assert_eq!(next_id(&mut stream), Some(1));
+        assert_eq!(fetched.load(Ordering::SeqCst), 2);
+        assert_eq!(next_id(&mut stream), Some(2));
+        assert_eq!(fetched.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn stop_on_error() {
+        let fetch: PageFetcher = Box::new(|_| {
+            Box::pin(async { Err(QueryError::Status(tonic::Status::unavailable("down"))) })
+        });
+        let query = Query::builder().query("SELECT id FROM table").build();
+        let mut stream = PagingStream::new(query, fetch);
+        assert!(futures_executor::block_on(stream.next()).unwrap().is_err());
+        assert!(futures_executor::block_on(stream.next()).is_none());
+    }
+}
@@ -1,8 +1,17 @@
 //! Utilities for building queries.
 
+use prost::Message;
+
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::error::{BatchTooLarge, BindMarkerMismatch, ParseConsistencyError};
 use crate::into_value::IntoValue;
 use crate::proto::{
-    Batch, BatchParameters, BatchQuery, Consistency, Query, QueryParameters, Value, Values,
+    Batch, BatchParameters, BatchQuery, Consistency, ConsistencyValue, Query, QueryParameters,
+    Value, Values,
 };
 
 impl From<Vec<Value>> for Values {
@@ -14,6 +23,109 @@ impl From<Vec<Value>> for Values {
     }
 }
 
+/// Post-processes the values bound to a query before they are put into
+/// the built [`Query`] or [`Batch`].
+///
+/// This is an extension point for unusual deployments, e.g. a proxy expecting
+/// a slightly different payload. Set it with [`QueryBuilder::payload_encoder`]
+/// or [`BatchBuilder::payload_encoder`]. The builders call it once for each query
+/// that has bound values. The values are then encoded with protobuf as usual when the
+/// request is sent. Without a custom encoder, [`IdentityEncoder`] is used and the values
+/// are sent as bound.
+///
+/// # Example
+/// ```
+/// use stargate_grpc::{Query, Value};
+/// use stargate_grpc::proto::Values;
+/// use stargate_grpc::query::PayloadEncoder;
+///
+/// /// Sends all bound values as a single list.
+/// struct WrapInList;
+///
+/// impl PayloadEncoder for WrapInList {
+///     fn encode(&self, values: Values) -> Values {
+///         Values::from(vec![Value::list(values.values)])
+///     }
+/// }
+///
+/// let query = Query::builder()
+///     .payload_encoder(WrapInList)
+///     .query("INSERT INTO table (ids) VALUES (?)")
+///     .bind((1, 2))
+///     .build();
+/// assert_eq!(query.values.unwrap().values, vec![Value::list(vec![1, 2])]);
+/// ```
+pub trait PayloadEncoder: Send + Sync {
+    /// Returns the values to send in place of the bound `values`.
+    fn encode(&self, values: Values) -> Values;
+}
+
+/// The default [`PayloadEncoder`], returning the values unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityEncoder;
+
+impl PayloadEncoder for IdentityEncoder {
+    fn encode(&self, values: Values) -> Values {
+        values
+    }
+}
+
+impl FromStr for Consistency {
+    type Err = ParseConsistencyError;
+
+    /// Parses the name of a consistency level, e.g. `"LOCAL_QUORUM"`.
+    /// Case is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "ANY" => Ok(Consistency::Any),
+            "ONE" => Ok(Consistency::One),
+            "TWO" => Ok(Consistency::Two),
+            "THREE" => Ok(Consistency::Three),
+            "QUORUM" => Ok(Consistency::Quorum),
+            "ALL" => Ok(Consistency::All),
+            "LOCAL_QUORUM" => Ok(Consistency::LocalQuorum),
+            "EACH_QUORUM" => Ok(Consistency::EachQuorum),
+            "SERIAL" => Ok(Consistency::Serial),
+            "LOCAL_SERIAL" => Ok(Consistency::LocalSerial),
+            "LOCAL_ONE" => Ok(Consistency::LocalOne),
+            _ => Err(ParseConsistencyError(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<&str> for Consistency {
+    type Error = ParseConsistencyError;
+
+    /// Parses the name of a consistency level, like [`FromStr`].
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl Consistency {
+    /// Returns true for the levels allowed as the serial consistency of
+    /// lightweight transactions: `SERIAL` and `LOCAL_SERIAL`.
+    pub fn is_serial(&self) -> bool {
+        matches!(self, Consistency::Serial | Consistency::LocalSerial)
+    }
+}
+
+/// Converts the serial consistency level, checking it is a serial one.
+fn serial_consistency_value(consistency: Consistency) -> ConsistencyValue {
+    assert!(
+        consistency.is_serial(),
+        "Serial consistency must be SERIAL or LOCAL_SERIAL, got {:?}",
+        consistency
+    );
+    consistency_value(consistency)
+}
+
+fn consistency_value(consistency: Consistency) -> ConsistencyValue {
+    ConsistencyValue {
+        value: consistency.into(),
+    }
+}
+
 /// Builds a [`Query`].
 /// Sets the CQL string, binds values and sets query execution parameters.
 ///
@@ -46,11 +158,21 @@ impl From<Vec<Value>> for Values {
 /// let query2 = query_defaults.clone().query("SELECT * FROM table2").build();
 /// ```
 ///
+/// Cloning the builder and calling [`build`](QueryBuilder::build) in a tight loop,
+/// e.g. in a bulk loader, doesn't encode anything. `build` only moves the bound values into
+/// the returned [`Query`], so the cost of each iteration is copying the query string and
+/// the parameters, plus converting the bound values.
+/// The protobuf encoding happens once per request, when the query is sent,
+/// into a buffer owned and reused by the gRPC codec.
+/// Use [`Query::encoded_size`] if you need to know the size of the encoded query upfront.
+/// To post-process the bound values before they are put into the query,
+/// set a [`PayloadEncoder`].
 #[derive(Default, Clone)]
 pub struct QueryBuilder {
     cql: Option<String>,
     values: ValuesBuilder,
     parameters: QueryParameters,
+    idempotent: Option<bool>,
 }
 
 impl QueryBuilder {
@@ -133,14 +255,56 @@ impl QueryBuilder {
     ///     .build();
     /// ```
     ///
+    /// Each name should be bound only once. Cassandra assigns the first value bound to a name
+    /// to all the bind markers with that name, and silently ignores the following ones.
+    ///
     /// # Panics
     /// Will panic if mixed with calls to [`bind`](QueryBuilder::bind)
     /// or [`bind_ith`](QueryBuilder::bind_ith).
+    /// In debug builds, will also panic if the same name is bound more than once.
     pub fn bind_name<T: Into<Value>>(mut self, name: &str, value: T) -> Self {
         self.values.bind_name(name, value);
         self
     }
 
+    /// Serializes a value to JSON and binds the resulting string to a name.
+    ///
+    /// Useful for storing whole documents in a single `text` column.
+    /// Use [`Value::try_deserialize_json`] to read them back.
+    /// Requires the `serde` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use stargate_grpc::Query;
+    ///
+    /// let mut settings = HashMap::new();
+    /// settings.insert("theme", "dark");
+    ///
+    /// let query = Query::builder()
+    ///     .query("INSERT INTO users(id, settings) VALUES (:id, :settings)")
+    ///     .bind_name("id", 1)
+    ///     .bind_name_json("settings", &settings)
+    ///     .unwrap()
+    ///     .build();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the value can't be serialized to JSON, e.g. if it is
+    /// a map with keys that are not strings.
+    ///
+    /// # Panics
+    /// Same as [`bind_name`](QueryBuilder::bind_name).
+    #[cfg(feature = "serde")]
+    pub fn bind_name_json<T: serde::Serialize + ?Sized>(
+        self,
+        name: &str,
+        value: &T,
+    ) -> Result<Self, serde_json::Error> {
+        let json = serde_json::to_string(value)?;
+        Ok(self.bind_name(name, json))
+    }
+
     /// Sets the keyspace the query will apply to.
     ///
     /// See [`QueryParameters::keyspace`].
@@ -158,21 +322,42 @@ impl QueryBuilder {
     ///     .query("SELECT * FROM table")
     ///     .consistency(Consistency::One);
     /// ```
+    ///
     /// See [`QueryParameters::consistency`].
     pub fn consistency(mut self, consistency: Consistency) -> Self {
-        self.parameters.consistency = Some(crate::proto::ConsistencyValue {
-            value: consistency.into(),
-        });
+        self.parameters.consistency = Some(consistency_value(consistency));
         self
     }
 
+    /// Sets the consistency level of the query, given by name, e.g. read from configuration.
+    ///
+    /// The name is parsed like with [`Consistency::from_str`], ignoring case.
+    ///
+    /// # Errors
+    /// Returns [`ParseConsistencyError`] if the name is not a known consistency level.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Query;
+    ///
+    /// let query = Query::builder()
+    ///     .query("SELECT * FROM table")
+    ///     .try_consistency("local_quorum")
+    ///     .unwrap();
+    /// assert!(Query::builder().try_consistency("local_qorum").is_err());
+    /// ```
+    pub fn try_consistency(self, consistency: &str) -> Result<Self, ParseConsistencyError> {
+        Ok(self.consistency(Consistency::try_from(consistency)?))
+    }
+
     /// Sets the serial consistency level (if the query is a lightweight transaction).
     ///
     /// See [`QueryParameters::serial_consistency`].
+    ///
+    /// # Panics
+    /// Will panic if the level is not [serial](Consistency::is_serial).
     pub fn serial_consistency(mut self, consistency: Consistency) -> Self {
-        self.parameters.serial_consistency = Some(crate::proto::ConsistencyValue {
-            value: consistency.into(),
-        });
+        self.parameters.serial_consistency = Some(serial_consistency_value(consistency));
         self
     }
 
@@ -215,6 +400,26 @@ impl QueryBuilder {
         QueryBuilder { parameters, ..self }
     }
 
+    /// Sets the [`PayloadEncoder`] applied to the bound values when the query is built.
+    ///
+    /// By default the values are left unchanged.
+    pub fn payload_encoder(mut self, encoder: impl PayloadEncoder + 'static) -> Self {
+        self.values.encoder = Some(Arc::new(encoder));
+        self
+    }
+
+    /// Marks the query as safe to execute more than once.
+    ///
+    /// The flag is not sent to the server; it is carried by the [`Statement`] returned from
+    /// [`build_statement`](QueryBuilder::build_statement) and tells
+    /// [`StargateClient::execute_statement`](crate::StargateClient::execute_statement)
+    /// whether the query may be retried after a failure.
+    /// If not set, only `SELECT` queries are considered idempotent.
+    pub fn idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = Some(idempotent);
+        self
+    }
+
     /// Builds the query that can be passed to
     /// [`StargateClient::execute_query`](crate::StargateClient::execute_query).
     ///
@@ -227,6 +432,140 @@ impl QueryBuilder {
             parameters: Some(self.parameters),
         }
     }
+
+    /// Builds the query together with its [`idempotent`](QueryBuilder::idempotent) flag.
+    ///
+    /// # Panics
+    /// Will panic if the query string was not set.
+    pub fn build_statement(self) -> Statement {
+        let idempotent = self.idempotent;
+        let query = self.build();
+        let idempotent = idempotent.unwrap_or_else(|| is_select(&query.cql));
+        Statement { query, idempotent }
+    }
+
+    /// Builds the query like [`build`](QueryBuilder::build), but first checks if
+    /// the values are bound the same way as the bind markers in the query string expect.
+    ///
+    /// Values bound by name, e.g. from a struct deriving `IntoValues`, can't be matched to
+    /// positional `?` markers, and the server would reject the query.
+    /// Conversely, values bound by position are assigned to named `:name` markers
+    /// in the order of the markers, ignoring the names. Although this is valid CQL,
+    /// it is rarely intended, so it is reported as well.
+    ///
+    /// The check is best-effort: it scans the query string for the markers,
+    /// skipping string literals, quoted identifiers and comments.
+    /// Queries with no bound values or with both kinds of markers are not checked.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Query;
+    /// use stargate_grpc::error::BindMarkerMismatch;
+    ///
+    /// let result = Query::builder()
+    ///     .query("SELECT * FROM users WHERE id = ?")
+    ///     .bind_name("id", 1)
+    ///     .build_checked();
+    /// assert_eq!(result.unwrap_err(), BindMarkerMismatch::NamedValuesForPositionalMarkers);
+    /// ```
+    ///
+    /// # Panics
+    /// Will panic if the query string was not set.
+    pub fn build_checked(self) -> Result<Query, BindMarkerMismatch> {
+        let cql = self.cql.as_deref().expect("cql string");
+        let values = &self.values;
+        if !values.values.is_empty() {
+            let markers = BindMarkers::scan(cql);
+            let named_values = !values.value_names.is_empty();
+            if named_values && markers.positional && !markers.named {
+                return Err(BindMarkerMismatch::NamedValuesForPositionalMarkers);
+            }
+            if !named_values && markers.named && !markers.positional {
+                return Err(BindMarkerMismatch::PositionalValuesForNamedMarkers);
+            }
+        }
+        Ok(self.build())
+    }
+}
+
+/// Kinds of bind markers found in a CQL string.
+#[derive(Debug, Default, PartialEq)]
+struct BindMarkers {
+    positional: bool,
+    named: bool,
+}
+
+impl BindMarkers {
+    /// Finds the bind markers in the CQL string, skipping string literals,
+    /// quoted identifiers and comments.
+    fn scan(cql: &str) -> BindMarkers {
+        let mut markers = BindMarkers::default();
+        for_each_bind_marker(cql, |_, name| match name {
+            None => markers.positional = true,
+            Some(_) => markers.named = true,
+        });
+        markers
+    }
+}
+
+/// Calls `f` with the byte range and the name of each bind marker in the CQL string,
+/// skipping string literals, quoted identifiers and comments.
+/// The name is `None` for positional `?` markers.
+fn for_each_bind_marker<'a>(cql: &'a str, mut f: impl FnMut(Range<usize>, Option<&'a str>)) {
+    let mut chars = cql.char_indices().peekable();
+    let mut prev = ' ';
+    while let Some((i, c)) = chars.next() {
+        match c {
+            // A quote inside a literal is escaped by doubling it, which the scanner
+            // sees as the end of one literal followed immediately by the start of another
+            '\'' | '"' => {
+                for (_, d) in chars.by_ref() {
+                    if d == c {
+                        break;
+                    }
+                }
+            }
+            '-' | '/' if matches!(chars.peek(), Some(&(_, d)) if d == c) => {
+                for (_, d) in chars.by_ref() {
+                    if d == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if matches!(chars.peek(), Some(&(_, '*'))) => {
+                chars.next();
+                let mut last = ' ';
+                for (_, d) in chars.by_ref() {
+                    if last == '*' && d == '/' {
+                        break;
+                    }
+                    last = d;
+                }
+            }
+            '?' => f(i..i + 1, None),
+            // Colons also appear in map and UDT literals, e.g. `{'a': 1}` or `{a: true}`,
+            // but there they usually follow the key directly
+            ':' if !is_identifier_char(prev) => {
+                if matches!(chars.peek(), Some(&(_, d)) if d.is_alphabetic() || d == '_') {
+                    let mut end = i + 1;
+                    while let Some(&(j, d)) = chars.peek() {
+                        if !(d.is_alphanumeric() || d == '_') {
+                            break;
+                        }
+                        end = j + d.len_utf8();
+                        chars.next();
+                    }
+                    f(i..end, Some(&cql[i + 1..end]));
+                }
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '\'' || c == '"'
 }
 
 impl Query {
@@ -234,6 +573,194 @@ impl Query {
     pub fn builder() -> QueryBuilder {
         QueryBuilder::new()
     }
+
+    /// Reconstructs a query from the CQL string, bound values and query parameters,
+    /// e.g. previously obtained from [`Query::into_parts`].
+    ///
+    /// If `values` is empty, the query is built without values, the same way
+    /// [`QueryBuilder::build`] does when nothing was bound.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::{Query, Value};
+    /// use stargate_grpc::proto::{QueryParameters, Values};
+    ///
+    /// let values = Values::from(vec![Value::bigint(1)]);
+    /// let query = Query::from_parts("SELECT * FROM users WHERE id = ?", values, QueryParameters::default());
+    /// assert_eq!(query.cql, "SELECT * FROM users WHERE id = ?");
+    /// ```
+    pub fn from_parts(cql: &str, values: Values, parameters: QueryParameters) -> Query {
+        Query {
+            cql: cql.to_string(),
+            values: if values.values.is_empty() {
+                None
+            } else {
+                Some(values)
+            },
+            parameters: Some(parameters),
+        }
+    }
+
+    /// Decomposes the query into the CQL string, bound values and query parameters.
+    /// Missing values or parameters are returned as their default (empty) counterparts.
+    ///
+    /// The query can be put back together with [`Query::from_parts`].
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Query;
+    ///
+    /// let query = Query::builder()
+    ///     .keyspace("ks")
+    ///     .query("SELECT * FROM users WHERE id = :id")
+    ///     .bind_name("id", 1000)
+    ///     .build();
+    ///
+    /// let (cql, values, parameters) = query.clone().into_parts();
+    /// assert_eq!(cql, "SELECT * FROM users WHERE id = :id");
+    /// assert_eq!(values.value_names, vec!["id".to_string()]);
+    /// assert_eq!(parameters.keyspace, Some("ks".to_string()));
+    ///
+    /// assert_eq!(Query::from_parts(&cql, values, parameters), query);
+    /// ```
+    pub fn into_parts(self) -> (String, Values, QueryParameters) {
+        (
+            self.cql,
+            self.values.unwrap_or_default(),
+            self.parameters.unwrap_or_default(),
+        )
+    }
+
+    /// Returns the exact number of bytes the query occupies when encoded
+    /// as a protobuf message, not counting the gRPC framing.
+    ///
+    /// Useful for logging or guarding the size of requests before sending them.
+    pub fn encoded_size(&self) -> usize {
+        self.encoded_len()
+    }
+
+    /// Renders the CQL string and the bound values, for audit logging of executed statements.
+    ///
+    /// The values are listed in square brackets after the CQL string, in the order they were
+    /// bound, formatted with the `Display` implementation of [`Value`].
+    /// Values bound by name are prefixed with their names.
+    /// If `redact` is true, each value is replaced by `?`, so the output doesn't
+    /// reveal any data, but still shows which values were bound.
+    /// A query without values is rendered as the CQL string alone.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Query;
+    ///
+    /// let query = Query::builder()
+    ///     .query("SELECT * FROM users WHERE login = :login")
+    ///     .bind_name("login", "admin")
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     query.to_audit_string(false),
+    ///     "SELECT * FROM users WHERE login = :login [values: login='admin']"
+    /// );
+    /// assert_eq!(
+    ///     query.to_audit_string(true),
+    ///     "SELECT * FROM users WHERE login = :login [values: login=?]"
+    /// );
+    /// ```
+    pub fn to_audit_string(&self, redact: bool) -> String {
+        audit_string(&self.cql, self.values.as_ref(), redact)
+    }
+
+    /// Renders the CQL string with the bound values inlined in place of the bind markers,
+    /// formatted with the `Display` implementation of [`Value`].
+    ///
+    /// This is meant only for displaying the query, e.g. when debugging.
+    /// **Never execute the returned string.** The values are not escaped in a way that
+    /// guarantees a valid and safe CQL statement, so executing it would open the door to
+    /// CQL injection. Send the original query with bound values instead.
+    ///
+    /// Named markers (`:name`) are replaced by the values bound to the same names.
+    /// If the values were bound by position, the markers are replaced by the values
+    /// in the order of the markers, whatever their kind, like the server does.
+    /// Markers with no corresponding value are left as they are.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Query;
+    ///
+    /// let query = Query::builder()
+    ///     .query("SELECT * FROM users WHERE login = :login AND age > :age")
+    ///     .bind_name("age", 18)
+    ///     .bind_name("login", "admin")
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     query.rendered_cql(),
+    ///     "SELECT * FROM users WHERE login = 'admin' AND age > 18"
+    /// );
+    /// ```
+    pub fn rendered_cql(&self) -> String {
+        let (names, values) = match &self.values {
+            Some(values) => (values.value_names.as_slice(), values.values.as_slice()),
+            None => (&[][..], &[][..]),
+        };
+        let mut rendered = String::with_capacity(self.cql.len());
+        let mut copied_up_to = 0;
+        let mut next_value = 0;
+        for_each_bind_marker(&self.cql, |marker, name| {
+            let value = match name {
+                Some(name) if !names.is_empty() => {
+                    names.iter().position(|n| n == name).map(|i| &values[i])
+                }
+                _ => {
+                    next_value += 1;
+                    values.get(next_value - 1)
+                }
+            };
+            if let Some(value) = value {
+                rendered.push_str(&self.cql[copied_up_to..marker.start]);
+                rendered.push_str(&value.to_string());
+                copied_up_to = marker.end;
+            }
+        });
+        rendered.push_str(&self.cql[copied_up_to..]);
+        rendered
+    }
+}
+
+/// A query together with the information whether it is safe to retry it.
+///
+/// A failed request doesn't tell whether the query was applied or not. For example,
+/// the coordinator might have written the data to the replicas, but the response was lost,
+/// or a write timed out after reaching some of the replicas. Executing such a query again is
+/// safe only if applying it twice has the same effect as applying it once.
+/// Retrying other queries, e.g. an `UPDATE` incrementing a counter, appending to a list
+/// or a lightweight transaction, could apply the same change twice.
+///
+/// Build it with [`QueryBuilder::build_statement`] or convert it from a [`Query`].
+/// A query converted without setting the flag explicitly is idempotent
+/// if it is a `SELECT` query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statement {
+    /// The query to execute.
+    pub query: Query,
+    /// Whether the query can be safely executed more than once.
+    pub idempotent: bool,
+}
+
+impl From<Query> for Statement {
+    fn from(query: Query) -> Self {
+        let idempotent = is_select(&query.cql);
+        Statement { query, idempotent }
+    }
+}
+
+/// Returns true if the first keyword of the CQL string is `SELECT`.
+fn is_select(cql: &str) -> bool {
+    let cql = cql.trim_start();
+    let keyword_end = cql
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(cql.len());
+    cql[..keyword_end].eq_ignore_ascii_case("select")
 }
 
 /// Builds a batch of queries.
@@ -275,6 +802,47 @@ impl BatchBuilder {
         self
     }
 
+    /// Adds a query prepared with a [`QueryBuilder`] to the batch, together with
+    /// the values bound in the builder.
+    ///
+    /// This allows to reuse the same functions for constructing standalone queries and
+    /// the queries of a batch. Only the CQL string and the values are taken from the builder.
+    /// Query parameters set on the builder, e.g. the keyspace or the consistency level,
+    /// are ignored, because the queries in a batch are executed with the parameters of the batch.
+    ///
+    /// The values are passed through the [`PayloadEncoder`] set on the `QueryBuilder`, if any.
+    /// Otherwise, the encoder set with [`payload_encoder`](BatchBuilder::payload_encoder)
+    /// on this batch builder is used.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::{Batch, Query};
+    ///
+    /// let insert = Query::builder()
+    ///     .query("INSERT INTO users (id, login) VALUES (?, ?)")
+    ///     .bind((1, "user"));
+    ///
+    /// let batch = Batch::builder()
+    ///     .keyspace("ks")
+    ///     .add(insert)
+    ///     .build();
+    /// ```
+    ///
+    /// # Panics
+    /// Will panic if the CQL string has not been set in the `QueryBuilder`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, mut query: QueryBuilder) -> Self {
+        self.finalize_query();
+        if query.values.encoder.is_none() {
+            query.values.encoder = self.values.encoder.clone();
+        }
+        self.built_queries.push(BatchQuery {
+            cql: query.cql.expect("cql string"),
+            values: query.values.build(),
+        });
+        self
+    }
+
     /// Binds all arguments of the lately added query at once,
     /// from a vector or a value that can be converted to a vector, e.g. a tuple.
     ///
@@ -300,9 +868,13 @@ impl BatchBuilder {
     ///
     /// This function can be called multiple times, to bind several arguments.
     ///
+    /// See [`QueryBuilder::bind_name`] for what happens if a name is bound more than once.
+    ///
     /// # Panics
     /// Will panic if mixed with calls to [`bind`](BatchBuilder::bind)
     /// or [`bind_ith`](BatchBuilder::bind_ith).
+    /// In debug builds, will also panic if the same name is bound more than once
+    /// in the same query.
     pub fn bind_name<T: Into<Value>>(mut self, name: &str, value: T) -> Self {
         self.values.bind_name(name, value);
         self
@@ -320,12 +892,18 @@ impl BatchBuilder {
     ///
     /// See [`BatchParameters::consistency`].
     pub fn consistency(mut self, consistency: Consistency) -> Self {
-        self.parameters.consistency = Some(crate::proto::ConsistencyValue {
-            value: consistency.into(),
-        });
+        self.parameters.consistency = Some(consistency_value(consistency));
         self
     }
 
+    /// Sets the consistency level of all queries in the batch, given by name.
+    ///
+    /// # Errors
+    /// Returns [`ParseConsistencyError`] if the name is not a known consistency level.
+    pub fn try_consistency(self, consistency: &str) -> Result<Self, ParseConsistencyError> {
+        Ok(self.consistency(Consistency::try_from(consistency)?))
+    }
+
     /// Sets whether the server should collect tracing information about the execution of the batch.
     ///
     /// See [`BatchParameters::tracing`].
@@ -345,10 +923,11 @@ impl BatchBuilder {
     /// Sets the serial consistency level (if the query is a lightweight transaction).
     ///
     /// See [`BatchParameters::serial_consistency`].
+    ///
+    /// # Panics
+    /// Will panic if the level is not [serial](Consistency::is_serial).
     pub fn serial_consistency(mut self, consistency: Consistency) -> Self {
-        self.parameters.serial_consistency = Some(crate::proto::ConsistencyValue {
-            value: consistency.into(),
-        });
+        self.parameters.serial_consistency = Some(serial_consistency_value(consistency));
         self
     }
 
@@ -360,6 +939,17 @@ impl BatchBuilder {
         self
     }
 
+    /// Sets the [`PayloadEncoder`] applied to the bound values of each query in the batch.
+    ///
+    /// Set it before adding the queries, so it applies to all of them.
+    /// Queries added with [`add`](BatchBuilder::add) use the encoder of their
+    /// `QueryBuilder` instead, if it has one.
+    /// By default the values are left unchanged.
+    pub fn payload_encoder(mut self, encoder: impl PayloadEncoder + 'static) -> Self {
+        self.values.encoder = Some(Arc::new(encoder));
+        self
+    }
+
     /// Finalizes building and returns the `Batch` that can be passed to
     /// [`StargateClient::execute_batch`](crate::StargateClient::execute_batch).
     pub fn build(mut self) -> Batch {
@@ -386,6 +976,109 @@ impl Batch {
     pub fn builder() -> BatchBuilder {
         BatchBuilder::new()
     }
+
+    /// Returns the exact number of bytes the batch occupies when encoded
+    /// as a protobuf message, not counting the gRPC framing.
+    ///
+    /// Useful for logging or guarding the size of requests before sending them.
+    pub fn encoded_size(&self) -> usize {
+        self.encoded_len()
+    }
+
+    /// Checks the number of statements and the [encoded size](Batch::encoded_size)
+    /// of the batch against the given limits.
+    ///
+    /// Allows to reject an oversized batch on the client side with a clear error,
+    /// before the server refuses to execute it.
+    /// The encoded size is only an estimate of the size of the mutations the server
+    /// compares against its threshold, so leave some margin when setting the limits.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::Batch;
+    /// use stargate_grpc::query::BatchLimits;
+    ///
+    /// let batch = Batch::builder()
+    ///     .query("INSERT INTO users (id, login) VALUES (?, ?)")
+    ///     .bind((0, "admin"))
+    ///     .build();
+    /// assert!(batch.validate(BatchLimits::default()).is_ok());
+    /// ```
+    pub fn validate(&self, limits: BatchLimits) -> Result<(), BatchTooLarge> {
+        let statements = self.queries.len();
+        if statements > limits.max_statements {
+            return Err(BatchTooLarge::TooManyStatements {
+                actual: statements,
+                limit: limits.max_statements,
+            });
+        }
+        let size = self.encoded_size();
+        if size > limits.max_size {
+            return Err(BatchTooLarge::TooManyBytes {
+                actual: size,
+                limit: limits.max_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Renders the CQL strings of all queries in the batch together with their bound values,
+    /// for audit logging. The queries are separated with `; `.
+    ///
+    /// See [`Query::to_audit_string`] for the format.
+    pub fn to_audit_string(&self, redact: bool) -> String {
+        self.queries
+            .iter()
+            .map(|q| audit_string(&q.cql, q.values.as_ref(), redact))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Limits checked by [`Batch::validate`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BatchLimits {
+    /// Maximum number of statements in a batch.
+    pub max_statements: usize,
+    /// Maximum encoded size of a batch, in bytes.
+    pub max_size: usize,
+}
+
+impl Default for BatchLimits {
+    /// Returns the limits matching the default Cassandra settings:
+    /// 65535 statements, which is the most the native protocol can carry,
+    /// and 50 KiB, which is the default `batch_size_fail_threshold_in_kb`.
+    fn default() -> Self {
+        BatchLimits {
+            max_statements: u16::MAX as usize,
+            max_size: 50 * 1024,
+        }
+    }
+}
+
+/// Renders the CQL string followed by the values, if there are any.
+fn audit_string(cql: &str, values: Option<&Values>, redact: bool) -> String {
+    let values = match values {
+        Some(values) if !values.values.is_empty() => values,
+        _ => return cql.to_string(),
+    };
+    let rendered: Vec<String> = values
+        .values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let value = if redact {
+                "?".to_string()
+            } else {
+                value.to_string()
+            };
+            match values.value_names.get(i) {
+                Some(name) => format!("{}={}", name, value),
+                None => value,
+            }
+        })
+        .collect();
+    format!("{} [values: {}]", cql, rendered.join(", "))
 }
 
 /// The logic of building the query argument values,
@@ -394,6 +1087,7 @@ impl Batch {
 struct ValuesBuilder {
     values: Vec<Value>,
     value_names: Vec<String>,
+    encoder: Option<Arc<dyn PayloadEncoder>>,
 }
 
 impl ValuesBuilder {
@@ -420,21 +1114,29 @@ impl ValuesBuilder {
         if self.values.len() != self.value_names.len() {
             panic!("Mixing named with non-named values is not allowed")
         }
+        if cfg!(debug_assertions) && self.value_names.iter().any(|n| n == name) {
+            panic!("Value already bound to name {}", name)
+        }
         self.value_names.push(name.to_string());
         self.values.push(value.into_value());
     }
 
     /// If there were any values bound with one of the `bind_` calls, moves them to the
-    /// return `Values` object. If no values were bound, returns `None`.
+    /// return `Values` object, passing them through the payload encoder, if set.
+    /// If no values were bound, returns `None`.
     /// After returning, `self` is left in a clean, empty state (the value vectors are cleared).
     /// This method can be called multiple times.
     pub fn build(&mut self) -> Option<Values> {
         if self.values.is_empty() {
             None
         } else {
-            Some(Values {
+            let values = Values {
                 values: self.values.drain(0..).collect(),
                 value_names: self.value_names.drain(0..).collect(),
+            };
+            Some(match &self.encoder {
+                Some(encoder) => encoder.encode(values),
+                None => values,
             })
         }
     }
@@ -442,9 +1144,13 @@ impl ValuesBuilder {
 
 #[cfg(test)]
 mod test {
+    use prost::Message;
+
+    use crate::error::{BatchTooLarge, BindMarkerMismatch};
+    use crate::proto::ConsistencyValue;
     use crate::proto::Values;
-    use crate::query::ValuesBuilder;
-    use crate::Value;
+    use crate::query::{BatchLimits, BindMarkers, PayloadEncoder, Statement, ValuesBuilder};
+    use crate::{Batch, Consistency, Query, Value};
 
     #[test]
     fn bind_a_single_item_tuple() {
@@ -505,4 +1211,580 @@ mod test {
             })
         );
     }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Value already bound to name a")]
+    fn bind_the_same_name_twice() {
+        let mut builder = ValuesBuilder::default();
+        builder.bind_name("a", 1);
+        builder.bind_name("b", 2);
+        builder.bind_name("a", 3);
+    }
+
+    #[test]
+    fn bind_the_same_name_in_different_batch_queries() {
+        let batch = Batch::builder()
+            .query("INSERT INTO users (id) VALUES (:id)")
+            .bind_name("id", 1)
+            .query("INSERT INTO users (id) VALUES (:id)")
+            .bind_name("id", 2)
+            .build();
+        assert_eq!(batch.queries.len(), 2);
+    }
+
+    #[test]
+    fn add_query_builder_to_batch() {
+        let insert = Query::builder()
+            .keyspace("ignored")
+            .query("INSERT INTO users (id, login) VALUES (?, ?)")
+            .bind((1, "user"));
+        let batch = Batch::builder()
+            .keyspace("ks")
+            .query("DELETE FROM users WHERE id = ?")
+            .bind((2,))
+            .add(insert)
+            .query("TRUNCATE logs")
+            .build();
+
+        assert_eq!(batch.queries.len(), 3);
+        assert_eq!(batch.queries[0].cql, "DELETE FROM users WHERE id = ?");
+        assert_eq!(
+            batch.queries[1].cql,
+            "INSERT INTO users (id, login) VALUES (?, ?)"
+        );
+        assert_eq!(
+            batch.queries[1].values,
+            Some(Values {
+                value_names: vec![],
+                values: vec![Value::bigint(1), Value::string("user")],
+            })
+        );
+        assert_eq!(batch.queries[2].cql, "TRUNCATE logs");
+        assert_eq!(batch.parameters.unwrap().keyspace, Some("ks".to_string()));
+    }
+
+    #[test]
+    fn query_round_trip_through_parts() {
+        let query = Query::builder()
+            .keyspace("ks")
+            .consistency(Consistency::LocalQuorum)
+            .page_size(100)
+            .query("SELECT * FROM table WHERE year = ? and month = ?")
+            .bind((2021, "October"))
+            .build();
+
+        let (cql, values, parameters) = query.clone().into_parts();
+        assert_eq!(
+            values.values,
+            vec![Value::bigint(2021), Value::string("October")]
+        );
+        assert_eq!(parameters.page_size, Some(100));
+
+        let rebuilt = Query::from_parts(&cql, values, parameters);
+        assert_eq!(rebuilt, query);
+    }
+
+    #[test]
+    fn query_without_values_round_trip_through_parts() {
+        let query = Query::builder().query("SELECT * FROM table").build();
+        let (cql, values, parameters) = query.clone().into_parts();
+        assert_eq!(values, Values::default());
+        assert_eq!(Query::from_parts(&cql, values, parameters), query);
+    }
+
+    #[test]
+    fn query_encoded_size() {
+        let query = Query::builder()
+            .keyspace("ks")
+            .query("SELECT * FROM table WHERE id = ?")
+            .bind((1000,))
+            .build();
+        assert_eq!(query.encoded_size(), query.encode_to_vec().len());
+    }
+
+    #[test]
+    fn repeated_builds_from_cloned_builder() {
+        let builder = Query::builder()
+            .keyspace("ks")
+            .query("INSERT INTO users (id, login) VALUES (?, ?)");
+        for id in 0..3 {
+            let query = builder.clone().bind((id, format!("user_{}", id))).build();
+            let expected = Query::builder()
+                .keyspace("ks")
+                .query("INSERT INTO users (id, login) VALUES (?, ?)")
+                .bind((id, format!("user_{}", id)))
+                .build();
+            assert_eq!(query, expected);
+            assert_eq!(query.encode_to_vec(), expected.encode_to_vec());
+        }
+        assert_eq!(builder.build().values, None);
+    }
+
+    #[test]
+    fn query_audit_string() {
+        let query = Query::builder()
+            .query("INSERT INTO users (id, login) VALUES (?, ?)")
+            .bind((1, "user"))
+            .build();
+        assert_eq!(
+            query.to_audit_string(false),
+            "INSERT INTO users (id, login) VALUES (?, ?) [values: 1, 'user']"
+        );
+        assert_eq!(
+            query.to_audit_string(true),
+            "INSERT INTO users (id, login) VALUES (?, ?) [values: ?, ?]"
+        );
+    }
+
+    #[test]
+    fn query_audit_string_without_values() {
+        let query = Query::builder().query("SELECT * FROM users").build();
+        assert_eq!(query.to_audit_string(false), "SELECT * FROM users");
+        assert_eq!(query.to_audit_string(true), "SELECT * FROM users");
+    }
+
+    #[test]
+    fn batch_audit_string() {
+        let batch = Batch::builder()
+            .query("DELETE FROM users WHERE id = :id")
+            .bind_name("id", 1)
+            .query("TRUNCATE logs")
+            .build();
+        assert_eq!(
+            batch.to_audit_string(false),
+            "DELETE FROM users WHERE id = :id [values: id=1]; TRUNCATE logs"
+        );
+        assert_eq!(
+            batch.to_audit_string(true),
+            "DELETE FROM users WHERE id = :id [values: id=?]; TRUNCATE logs"
+        );
+    }
+
+    #[test]
+    fn batch_encoded_size() {
+        let batch = Batch::builder()
+            .keyspace("ks")
+            .query("INSERT INTO users (id, login) VALUES (?, ?)")
+            .bind((0, "admin"))
+            .query("INSERT INTO users (id, login) VALUES (?, ?)")
+            .bind((1, "user"))
+            .build();
+        assert_eq!(batch.encoded_size(), batch.encode_to_vec().len());
+    }
+
+    #[test]
+    fn validate_batch_within_limits() {
+        let batch = Batch::builder()
+            .query("INSERT INTO users (id, login) VALUES (?, ?)")
+            .bind((0, "admin"))
+            .query("INSERT INTO users (id, login) VALUES (?, ?)")
+            .bind((1, "user"))
+            .build();
+        assert_eq!(batch.validate(BatchLimits::default()), Ok(()));
+        let limits = BatchLimits {
+            max_statements: 2,
+            max_size: batch.encoded_size(),
+        };
+        assert_eq!(batch.validate(limits), Ok(()));
+    }
+
+    #[test]
+    fn reject_batch_over_limits() {
+        let mut builder = Batch::builder();
+        for id in 0..3 {
+            builder = builder
+                .query("INSERT INTO users (id, login) VALUES (?, ?)")
+                .bind((id, "x".repeat(1000)));
+        }
+        let batch = builder.build();
+        let size = batch.encoded_size();
+
+        let limits = BatchLimits {
+            max_statements: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            batch.validate(limits),
+            Err(BatchTooLarge::TooManyStatements {
+                actual: 3,
+                limit: 2
+            })
+        );
+        let limits = BatchLimits {
+            max_size: 1024,
+            ..Default::default()
+        };
+        assert_eq!(
+            batch.validate(limits),
+            Err(BatchTooLarge::TooManyBytes {
+                actual: size,
+                limit: 1024
+            })
+        );
+    }
+
+    #[test]
+    fn only_select_statements_are_idempotent_by_default() {
+        let select = Query::builder().query("  select * FROM users").build();
+        assert!(Statement::from(select).idempotent);
+        let insert = Query::builder()
+            .query("INSERT INTO users (id) VALUES (1)")
+            .build();
+        assert!(!Statement::from(insert).idempotent);
+
+        let statement = Query::builder()
+            .query("SELECT * FROM users")
+            .build_statement();
+        assert!(statement.idempotent);
+        let statement = Query::builder()
+            .query("UPDATE users SET login = 'admin' WHERE id = 1")
+            .idempotent(true)
+            .build_statement();
+        assert!(statement.idempotent);
+        let statement = Query::builder()
+            .query("SELECT * FROM users")
+            .idempotent(false)
+            .build_statement();
+        assert!(!statement.idempotent);
+    }
+
+    #[test]
+    fn parse_consistency() {
+        assert_eq!("ONE".parse::<Consistency>().unwrap(), Consistency::One);
+        assert_eq!(
+            "local_quorum".parse::<Consistency>().unwrap(),
+            Consistency::LocalQuorum
+        );
+        assert!("QUORUM_LOCAL".parse::<Consistency>().is_err());
+    }
+
+    #[test]
+    fn set_consistency_from_enum_or_string() {
+        let from_enum = Query::builder()
+            .query("SELECT * FROM table")
+            .consistency(Consistency::LocalQuorum)
+            .serial_consistency(Consistency::LocalSerial)
+            .build();
+        let from_string = Query::builder()
+            .query("SELECT * FROM table")
+            .try_consistency("LOCAL_QUORUM")
+            .unwrap()
+            .serial_consistency("local_serial".parse::<Consistency>().unwrap())
+            .build();
+        assert_eq!(from_enum, from_string);
+        let parameters = from_string.parameters.unwrap();
+        assert_eq!(
+            parameters.consistency,
+            Some(ConsistencyValue {
+                value: Consistency::LocalQuorum.into()
+            })
+        );
+    }
+
+    #[test]
+    fn set_serial_consistency() {
+        let query = Query::builder()
+            .query("UPDATE users SET login = 'foo' WHERE id = 1 IF EXISTS")
+            .serial_consistency(Consistency::LocalSerial)
+            .build();
+        let parameters = query.parameters.unwrap();
+        assert_eq!(
+            parameters.serial_consistency,
+            Some(ConsistencyValue {
+                value: Consistency::LocalSerial.into()
+            })
+        );
+        assert_eq!(parameters.consistency, None);
+    }
+
+    #[test]
+    fn serial_consistency_levels() {
+        assert!(Consistency::Serial.is_serial());
+        assert!(Consistency::LocalSerial.is_serial());
+        assert!(!Consistency::Quorum.is_serial());
+        assert!(!Consistency::LocalOne.is_serial());
+    }
+
+    #[test]
+    #[should_panic(expected = "Serial consistency must be SERIAL or LOCAL_SERIAL")]
+    fn reject_non_serial_serial_consistency() {
+        Query::builder()
+            .query("UPDATE users SET login = 'foo' WHERE id = 1 IF EXISTS")
+            .serial_consistency(Consistency::LocalQuorum);
+    }
+
+    #[test]
+    #[should_panic(expected = "Serial consistency must be SERIAL or LOCAL_SERIAL")]
+    fn reject_non_serial_batch_serial_consistency() {
+        Batch::builder().serial_consistency(Consistency::One);
+    }
+
+    #[test]
+    fn set_batch_consistency_from_enum_or_string() {
+        let from_enum = Batch::builder()
+            .consistency(Consistency::Quorum)
+            .serial_consistency(Consistency::Serial)
+            .build();
+        let from_string = Batch::builder()
+            .try_consistency("quorum")
+            .unwrap()
+            .serial_consistency("SERIAL".parse::<Consistency>().unwrap())
+            .build();
+        assert_eq!(from_enum, from_string);
+    }
+
+    #[test]
+    fn reject_unknown_consistency_name() {
+        let error = Query::builder().try_consistency("QUORUM_LOCAL").err();
+        assert_eq!(error.unwrap().0, "QUORUM_LOCAL");
+        assert!(Batch::builder().try_consistency("").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn bind_json_document_round_trip() {
+        use crate::error::ConversionErrorKind;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Document {
+            title: String,
+            tags: Vec<String>,
+            rating: Option<f64>,
+        }
+
+        let document = Document {
+            title: "Cassandra".to_string(),
+            tags: vec!["database".to_string(), "nosql".to_string()],
+            rating: Some(4.5),
+        };
+        let query = Query::builder()
+            .query("INSERT INTO docs(id, body) VALUES (:id, :body)")
+            .bind_name("id", 1)
+            .bind_name_json("body", &document)
+            .unwrap()
+            .build();
+
+        // simulate reading the value back from the text column
+        let values = query.values.unwrap();
+        let body = values.values[1].clone();
+        assert_eq!(values.value_names[1], "body");
+        assert!(matches!(
+            body.inner,
+            Some(crate::proto::value::Inner::String(_))
+        ));
+        let read: Document = body.try_deserialize_json().unwrap();
+        assert_eq!(read, document);
+
+        let error = Value::string("{").try_deserialize_json::<Document>();
+        assert!(matches!(
+            error.unwrap_err().kind,
+            ConversionErrorKind::InvalidJson(_)
+        ));
+        let error = Value::bigint(1).try_deserialize_json::<Document>();
+        assert_eq!(error.unwrap_err().kind, ConversionErrorKind::Incompatible);
+    }
+
+    #[test]
+    fn detect_named_values_for_positional_markers() {
+        let result = Query::builder()
+            .query("SELECT * FROM users WHERE id = ? AND login = ?")
+            .bind_name("id", 1)
+            .bind_name("login", "user_1")
+            .build_checked();
+        assert_eq!(
+            result.unwrap_err(),
+            BindMarkerMismatch::NamedValuesForPositionalMarkers
+        );
+    }
+
+    #[test]
+    fn detect_positional_values_for_named_markers() {
+        let result = Query::builder()
+            .query("SELECT * FROM users WHERE id = :id AND login = :login")
+            .bind((1, "user_1"))
+            .build_checked();
+        assert_eq!(
+            result.unwrap_err(),
+            BindMarkerMismatch::PositionalValuesForNamedMarkers
+        );
+    }
+
+    #[test]
+    fn accept_matching_markers() {
+        let positional = Query::builder()
+            .query("SELECT * FROM users WHERE id = ?")
+            .bind((1,))
+            .build_checked();
+        assert!(positional.is_ok());
+        let named = Query::builder()
+            .query("SELECT * FROM users WHERE id=:id")
+            .bind_name("id", 1)
+            .build_checked();
+        assert!(named.is_ok());
+        let no_values = Query::builder()
+            .query("SELECT * FROM users WHERE id = ?")
+            .build_checked();
+        assert!(no_values.is_ok());
+    }
+
+    #[test]
+    fn render_query_with_inlined_values() {
+        let query = Query::builder()
+            .query("UPDATE users SET login = ?, tags = ? WHERE id = ? AND note = 'why?'")
+            .bind(("o'hara", vec!["a", "b"], 10))
+            .build();
+        assert_eq!(
+            query.rendered_cql(),
+            "UPDATE users SET login = 'o''hara', tags = ['a', 'b'] WHERE id = 10 AND note = 'why?'"
+        );
+
+        let query = Query::builder()
+            .query("SELECT * FROM users WHERE id = :id AND login = :login -- :id")
+            .bind_name("login", "admin")
+            .bind_name("id", 1)
+            .build();
+        assert_eq!(
+            query.rendered_cql(),
+            "SELECT * FROM users WHERE id = 1 AND login = 'admin' -- :id"
+        );
+    }
+
+    #[test]
+    fn render_query_with_mixed_and_unbound_markers() {
+        // values bound by position go to the markers in order, whatever their kind:
+        let query = Query::builder()
+            .query("SELECT * FROM t WHERE a = ? AND b = :b AND c = ?")
+            .bind((1, 2))
+            .build();
+        assert_eq!(
+            query.rendered_cql(),
+            "SELECT * FROM t WHERE a = 1 AND b = 2 AND c = ?"
+        );
+
+        let query = Query::builder()
+            .query("SELECT * FROM t WHERE a = :a AND b = :b")
+            .bind_name("b", 2)
+            .build();
+        assert_eq!(
+            query.rendered_cql(),
+            "SELECT * FROM t WHERE a = :a AND b = 2"
+        );
+
+        let query = Query::builder()
+            .query("SELECT * FROM t WHERE a = ?")
+            .build();
+        assert_eq!(query.rendered_cql(), "SELECT * FROM t WHERE a = ?");
+    }
+
+    /// Wraps the bound values in a single blob holding their protobuf encoding.
+    struct WrapInBlob;
+
+    impl PayloadEncoder for WrapInBlob {
+        fn encode(&self, values: Values) -> Values {
+            Values::from(vec![Value::bytes(values.encode_to_vec())])
+        }
+    }
+
+    #[test]
+    fn apply_payload_encoder_to_query_values() {
+        let builder = Query::builder()
+            .query("INSERT INTO t (a, b) VALUES (?, ?)")
+            .bind((1, "x"));
+        let plain = builder.clone().build();
+        let wrapped = builder.payload_encoder(WrapInBlob).build();
+
+        let expected = Value::bytes(plain.values.clone().unwrap().encode_to_vec());
+        assert_eq!(wrapped.values.unwrap().values, vec![expected]);
+        assert_eq!(wrapped.cql, plain.cql);
+        assert_eq!(wrapped.parameters, plain.parameters);
+    }
+
+    #[test]
+    fn apply_payload_encoder_to_batch_values() {
+        let batch = Batch::builder()
+            .payload_encoder(WrapInBlob)
+            .query("INSERT INTO t (a) VALUES (?)")
+            .bind((1,))
+            .query("TRUNCATE t")
+            .query("INSERT INTO t (a) VALUES (?)")
+            .bind((2,))
+            .build();
+
+        let wrapped = |value: i64| {
+            let values = Values::from(vec![Value::bigint(value)]);
+            Some(Values::from(vec![Value::bytes(values.encode_to_vec())]))
+        };
+        assert_eq!(batch.queries[0].values, wrapped(1));
+        assert_eq!(batch.queries[1].values, None);
+        assert_eq!(batch.queries[2].values, wrapped(2));
+    }
+
+    #[test]
+    fn apply_batch_payload_encoder_to_added_queries() {
+        /// Replaces the bound values with a single marker value.
+        struct Replace;
+
+        impl PayloadEncoder for Replace {
+            fn encode(&self, _values: Values) -> Values {
+                Values::from(vec![Value::string("replaced")])
+            }
+        }
+
+        let insert = |value: i64| {
+            Query::builder()
+                .query("INSERT INTO t (a) VALUES (?)")
+                .bind((value,))
+        };
+        let batch = Batch::builder()
+            .payload_encoder(WrapInBlob)
+            .add(insert(1))
+            .add(insert(2).payload_encoder(Replace))
+            .build();
+
+        let values = Values::from(vec![Value::bigint(1)]);
+        let wrapped = Values::from(vec![Value::bytes(values.encode_to_vec())]);
+        assert_eq!(batch.queries[0].values, Some(wrapped));
+        // the encoder of the query builder takes precedence:
+        assert_eq!(
+            batch.queries[1].values,
+            Some(Values::from(vec![Value::string("replaced")]))
+        );
+    }
+
+    #[test]
+    fn build_values_unchanged_by_default() {
+        let query = Query::builder()
+            .query("INSERT INTO t (a) VALUES (?)")
+            .bind((1,))
+            .build();
+        assert_eq!(query.values.unwrap().values, vec![Value::bigint(1)]);
+    }
+
+    #[test]
+    fn scan_bind_markers() {
+        let scan = |cql| {
+            let markers = BindMarkers::scan(cql);
+            (markers.positional, markers.named)
+        };
+        assert_eq!(scan("SELECT * FROM t WHERE a = ?"), (true, false));
+        assert_eq!(scan("SELECT * FROM t WHERE a = :a"), (false, true));
+        assert_eq!(scan("SELECT * FROM t WHERE a = :a AND b = ?"), (true, true));
+        assert_eq!(
+            scan("SELECT * FROM t WHERE a = 'why?' AND b = ':b'"),
+            (false, false)
+        );
+        assert_eq!(scan("SELECT * FROM t WHERE a = 'it''s?'"), (false, false));
+        assert_eq!(scan("SELECT \"what?\" FROM t"), (false, false));
+        assert_eq!(scan("SELECT * FROM t -- why?\nWHERE a = :a"), (false, true));
+        assert_eq!(
+            scan("SELECT * FROM t /* :b ? */ WHERE a = ?"),
+            (true, false)
+        );
+        assert_eq!(
+            scan("INSERT INTO t(m, u) VALUES ({'a':1}, {b:true})"),
+            (false, false)
+        );
+    }
 }
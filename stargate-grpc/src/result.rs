@@ -2,8 +2,9 @@
 
 use crate::error::ConversionError;
 use crate::from_value::TryFromValue;
-use crate::proto::{Response, ResultSet, Row};
-use std::collections::HashMap;
+use crate::proto::type_spec::{Basic, Spec};
+use crate::proto::{ColumnSpec, Response, ResultSet, Row, TypeSpec, Value};
+use std::collections::{BTreeMap, HashMap};
 
 use std::convert::TryFrom;
 use std::error::Error;
@@ -95,6 +96,24 @@ impl Row {
 #[derive(Debug)]
 pub enum MapperError {
     ColumnNotFound(&'static str),
+    /// When columns are matched ignoring case, and more than one column matches the name.
+    AmbiguousColumn(&'static str),
+    /// When more than one field would be read from the same column,
+    /// e.g. a field of the struct and a field of a `#[stargate(flatten)]` struct.
+    DuplicateColumn(&'static str),
+    /// When `ColumnPositions::column_names` of the given type doesn't return
+    /// one name per column position returned by `ColumnPositions::field_to_column_pos`.
+    ColumnNamesMismatch {
+        type_name: &'static str,
+        names: usize,
+        positions: usize,
+    },
+    /// When the names or the order of the columns in the `ResultSet`
+    /// do not match the expected ones.
+    UnexpectedColumns {
+        expected: Vec<String>,
+        actual: Vec<String>,
+    },
 }
 
 impl Display for MapperError {
@@ -103,6 +122,30 @@ impl Display for MapperError {
             MapperError::ColumnNotFound(name) => {
                 write!(f, "Column {} not found in the ResultSet", name)
             }
+            MapperError::AmbiguousColumn(name) => {
+                write!(f, "Column {} is ambiguous when ignoring case", name)
+            }
+            MapperError::DuplicateColumn(name) => {
+                write!(f, "Column {} is read by more than one field", name)
+            }
+            MapperError::ColumnNamesMismatch {
+                type_name,
+                names,
+                positions,
+            } => {
+                write!(
+                    f,
+                    "{} reports {} column names for {} column positions",
+                    type_name, names, positions
+                )
+            }
+            MapperError::UnexpectedColumns { expected, actual } => {
+                write!(
+                    f,
+                    "Expected columns {:?} but the ResultSet contains {:?}",
+                    expected, actual
+                )
+            }
         }
     }
 }
@@ -114,6 +157,95 @@ pub trait ColumnPositions {
     fn field_to_column_pos(
         column_positions: HashMap<String, usize>,
     ) -> Result<Vec<usize>, MapperError>;
+
+    /// Returns the names of the columns read by the conversion,
+    /// one name for each position returned by `field_to_column_pos`, in the same order.
+    ///
+    /// Use it to keep the list of selected columns in sync with the type definition.
+    /// Implementations derived for structs with `#[stargate(flatten)]` fields build the list
+    /// once, on the first call, because the names of the nested struct are not known
+    /// at compile time.
+    fn column_names() -> &'static [&'static str];
+}
+
+/// Finds the position of the column with the given name, ignoring ASCII case.
+///
+/// A column matching the name exactly takes precedence. Otherwise, returns the position
+/// of the only column with the same name ignoring case. If there are several such columns,
+/// e.g. `"Login"` and `"LOGIN"` when looking for `"login"`, there is no way to tell which one
+/// is meant, so [`MapperError::AmbiguousColumn`] is returned.
+///
+/// Used by the code generated for `#[stargate(case_insensitive)]` structs.
+pub fn column_position_ignore_case(
+    column_positions: &HashMap<String, usize>,
+    name: &'static str,
+) -> Result<usize, MapperError> {
+    if let Some(pos) = column_positions.get(name) {
+        return Ok(*pos);
+    }
+    let mut matches = column_positions
+        .iter()
+        .filter(|(column, _)| column.eq_ignore_ascii_case(name));
+    match (matches.next(), matches.next()) {
+        (Some((_, pos)), None) => Ok(*pos),
+        (Some(_), Some(_)) => Err(MapperError::AmbiguousColumn(name)),
+        (None, _) => Err(MapperError::ColumnNotFound(name)),
+    }
+}
+
+/// Returns the column positions of a type read with `#[stargate(flatten)]`.
+///
+/// The outer struct relies on [`ColumnPositions::column_names`] to tell how many positions
+/// belong to the nested type, so the nested type must return one name per position.
+///
+/// # Errors
+/// Returns [`MapperError::ColumnNamesMismatch`] if `T::column_names()` and
+/// `T::field_to_column_pos` return a different number of items.
+///
+/// Used by the code generated for `#[stargate(flatten)]` fields.
+pub fn nested_column_positions<T: ColumnPositions>(
+    column_positions: HashMap<String, usize>,
+) -> Result<Vec<usize>, MapperError> {
+    let positions = T::field_to_column_pos(column_positions)?;
+    check_column_names::<T>(&positions)?;
+    Ok(positions)
+}
+
+/// Checks that `T` reports one column name per column position.
+fn check_column_names<T: ColumnPositions>(positions: &[usize]) -> Result<(), MapperError> {
+    let names = T::column_names().len();
+    if names == positions.len() {
+        Ok(())
+    } else {
+        Err(MapperError::ColumnNamesMismatch {
+            type_name: std::any::type_name::<T>(),
+            names,
+            positions: positions.len(),
+        })
+    }
+}
+
+/// Checks that no column position is used more than once.
+///
+/// Reading a value takes it out of the row, so a second read of the same column
+/// would get an empty value.
+///
+/// # Errors
+/// Returns [`MapperError::DuplicateColumn`] with the name of the column read again, or
+/// [`MapperError::ColumnNamesMismatch`] if `T` doesn't report one name per position.
+///
+/// Used by the code generated for `TryFromRow` structs.
+pub fn ensure_distinct_positions<T: ColumnPositions>(
+    positions: &[usize],
+) -> Result<(), MapperError> {
+    check_column_names::<T>(positions)?;
+    let names = T::column_names();
+    for (i, pos) in positions.iter().enumerate() {
+        if positions[..i].contains(pos) {
+            return Err(MapperError::DuplicateColumn(names[i]));
+        }
+    }
+    Ok(())
 }
 
 /// Converts rows to values of user type
@@ -134,6 +266,32 @@ where
     /// This function is allowed to panic if the row is not large enough to contain the item
     /// at maximum index pointed to by `column_positions`.
     fn try_unpack(row: Row, column_positions: &[usize]) -> Result<Self, ConversionError>;
+
+    /// Converts a part of the `row` into `Self`, taking the values at the positions
+    /// read from the `column_positions` iterator and leaving the other values in the row.
+    ///
+    /// Used to read structs nested in another struct with `#[stargate(flatten)]`.
+    /// Implementations must consume only the positions of their own columns
+    /// and take only the values at these positions,
+    /// so the remaining positions and values can be used by the outer struct.
+    /// The default implementation takes as many positions as
+    /// [`ColumnPositions::column_names`] returns names, moves the values at these positions
+    /// into a new row and passes it to [`try_unpack`](TryFromRow::try_unpack).
+    fn try_unpack_nested(
+        row: &mut Row,
+        column_positions: &mut std::slice::Iter<'_, usize>,
+    ) -> Result<Self, ConversionError>
+    where
+        Self: ColumnPositions,
+    {
+        let count = Self::column_names().len();
+        let values = column_positions
+            .take(count)
+            .map(|pos| std::mem::take(&mut row.values[*pos]))
+            .collect::<Vec<_>>();
+        let positions = (0..values.len()).collect::<Vec<_>>();
+        Self::try_unpack(Row { values }, &positions)
+    }
 }
 
 /// `ResultSetMapper` coverts a `Row` into `T`.
@@ -183,6 +341,28 @@ impl<T: TryFromRow> ResultSetMapper<T> {
     }
 }
 
+/// Iterator over the rows of a result set converted to values of type `T`.
+///
+/// Call [`ResultSet::into_typed`] to obtain one.
+pub struct TypedRows<T> {
+    mapper: ResultSetMapper<T>,
+    rows: std::vec::IntoIter<Row>,
+}
+
+impl<T: TryFromRow> Iterator for TypedRows<T> {
+    type Item = Result<T, ConversionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next().map(|row| self.mapper.try_unpack(row))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl<T: TryFromRow> ExactSizeIterator for TypedRows<T> {}
+
 impl ResultSet {
     /// Creates a mapper that can convert `Row`s to values of type `T`.
     ///
@@ -245,6 +425,322 @@ impl ResultSet {
         })
     }
 
+    /// Consumes the result set and returns an iterator converting its rows to values of type `T`.
+    ///
+    /// Combines [`mapper`](ResultSet::mapper) and [`ResultSetMapper::try_unpack`],
+    /// so the returned iterator can be used directly with `collect`, `filter_map` etc.
+    /// Each row is converted when the iterator reaches it.
+    ///
+    /// # Errors
+    /// Returns `MapperError` if the result set doesn't contain all columns required
+    /// to construct values of type `T`.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "macros")]
+    /// # {
+    /// use stargate_grpc::*;
+    /// use stargate_grpc::error::ConversionError;
+    /// use stargate_grpc::proto::*;
+    ///
+    /// let result_set = ResultSet {
+    ///     columns: vec![ColumnSpec { r#type: None, name: "id".to_string() }],
+    ///     rows: vec![
+    ///         Row { values: vec![Value::int(1)] },
+    ///         Row { values: vec![Value::int(2)] },
+    ///     ],
+    ///     paging_state: None,
+    /// };
+    ///
+    /// #[derive(TryFromRow)]
+    /// struct User {
+    ///     id: i64,
+    /// }
+    ///
+    /// let users: Result<Vec<User>, ConversionError> = result_set.into_typed().unwrap().collect();
+    /// assert_eq!(users.unwrap().len(), 2);
+    /// # }
+    /// ```
+    pub fn into_typed<T>(self) -> Result<TypedRows<T>, MapperError>
+    where
+        T: ColumnPositions + TryFromRow,
+    {
+        let mapper = self.mapper()?;
+        Ok(TypedRows {
+            mapper,
+            rows: self.rows.into_iter(),
+        })
+    }
+
+    /// Checks if the result set contains exactly the given columns, in the given order.
+    ///
+    /// Unpacking rows into tuples relies on the positions of the columns only.
+    /// If the query selects all columns with `SELECT *`, a schema change may reorder
+    /// the columns and the values would be silently assigned to wrong tuple elements.
+    /// Call this function before unpacking the rows to guard against that.
+    ///
+    /// # Errors
+    /// Returns [`MapperError::UnexpectedColumns`] if the column names or their order differ.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::ResultSet;
+    /// use stargate_grpc::proto::ColumnSpec;
+    ///
+    /// fn column(name: &str) -> ColumnSpec {
+    ///     ColumnSpec {
+    ///         r#type: None,
+    ///         name: name.to_string(),
+    ///     }
+    /// }
+    /// let result_set = ResultSet {
+    ///     columns: vec![column("id"), column("login")],
+    ///     rows: vec![],
+    ///     paging_state: None,
+    /// };
+    ///
+    /// assert!(result_set.expect_columns(&["id", "login"]).is_ok());
+    /// assert!(result_set.expect_columns(&["login", "id"]).is_err());
+    /// ```
+    pub fn expect_columns(&self, names: &[&str]) -> Result<(), MapperError> {
+        let matches = self.columns.len() == names.len()
+            && self
+                .columns
+                .iter()
+                .zip(names)
+                .all(|(column, name)| column.name == *name);
+        if matches {
+            Ok(())
+        } else {
+            Err(MapperError::UnexpectedColumns {
+                expected: names.iter().map(|n| n.to_string()).collect(),
+                actual: self.columns.iter().map(|c| c.name.clone()).collect(),
+            })
+        }
+    }
+
+    /// Consumes the result set and returns a result set with only the given columns,
+    /// in the given order.
+    ///
+    /// Useful for dropping the columns that are not needed before further processing,
+    /// so the values of wide rows don't have to be kept in memory.
+    /// The paging state is preserved.
+    ///
+    /// The kept values are moved into the new rows. They are cloned only if
+    /// the same name is given more than once.
+    ///
+    /// # Errors
+    /// Returns [`MapperError::UnexpectedColumns`] if any of the names doesn't match a column.
+    ///
+    /// # Panics
+    /// If a row has fewer values than the result set has columns.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::{ResultSet, Row, Value};
+    /// use stargate_grpc::proto::ColumnSpec;
+    ///
+    /// fn column(name: &str) -> ColumnSpec {
+    ///     ColumnSpec {
+    ///         r#type: None,
+    ///         name: name.to_string(),
+    ///     }
+    /// }
+    /// let result_set = ResultSet {
+    ///     columns: vec![column("id"), column("login"), column("bio")],
+    ///     rows: vec![Row {
+    ///         values: vec![Value::bigint(1), Value::string("admin"), Value::string("...")],
+    ///     }],
+    ///     paging_state: None,
+    /// };
+    ///
+    /// let result_set = result_set.project(&["login", "id"]).unwrap();
+    /// assert_eq!(result_set.rows[0].values, vec![Value::string("admin"), Value::bigint(1)]);
+    /// ```
+    pub fn project(self, names: &[&str]) -> Result<ResultSet, MapperError> {
+        let column_positions = self.column_positions();
+        let positions = names
+            .iter()
+            .map(|name| column_positions.get(*name).copied())
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(|| MapperError::UnexpectedColumns {
+                expected: names.iter().map(|n| n.to_string()).collect(),
+                actual: self.columns.iter().map(|c| c.name.clone()).collect(),
+            })?;
+        // The last use of a position can move the item out, the previous ones must clone it:
+        let repeated: Vec<bool> = (0..positions.len())
+            .map(|i| positions[i + 1..].contains(&positions[i]))
+            .collect();
+        fn take_or_clone<T: Clone + Default>(item: &mut T, clone: bool) -> T {
+            if clone {
+                item.clone()
+            } else {
+                std::mem::take(item)
+            }
+        }
+        let mut columns = self.columns;
+        let columns = positions
+            .iter()
+            .zip(&repeated)
+            .map(|(&pos, &clone)| take_or_clone(&mut columns[pos], clone))
+            .collect();
+        let rows = self
+            .rows
+            .into_iter()
+            .map(|mut row| Row {
+                values: positions
+                    .iter()
+                    .zip(&repeated)
+                    .map(|(&pos, &clone)| take_or_clone(&mut row.values[pos], clone))
+                    .collect(),
+            })
+            .collect();
+        Ok(ResultSet {
+            columns,
+            rows,
+            paging_state: self.paging_state,
+        })
+    }
+
+    /// Returns true if the query returned more rows than fit in this page.
+    ///
+    /// To fetch the next page, pass the paging state of this page to the next query,
+    /// e.g. with [`take_paging_state`](ResultSet::take_paging_state).
+    pub fn has_more_pages(&self) -> bool {
+        matches!(&self.paging_state, Some(state) if !state.is_empty())
+    }
+
+    /// Removes the paging state from the result set and returns it if there are more pages.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::convert::TryInto;
+    /// use stargate_grpc::{Query, ResultSet, StargateClient};
+    ///
+    /// # async fn scan(client: &mut StargateClient) -> anyhow::Result<()> {
+    /// let mut builder = Query::builder().query("SELECT id FROM users");
+    /// loop {
+    ///     let query = builder.clone().build();
+    ///     let mut result_set: ResultSet = client.execute_query(query).await?.try_into()?;
+    ///     // ... process result_set.rows
+    ///     match result_set.take_paging_state() {
+    ///         Some(paging_state) => builder = builder.paging_state(paging_state),
+    ///         None => break,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn take_paging_state(&mut self) -> Option<Vec<u8>> {
+        self.paging_state.take().filter(|state| !state.is_empty())
+    }
+
+    /// Returns the names and the decoded types of the columns, in the order of values in rows.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::ResultSet;
+    /// use stargate_grpc::proto::{ColumnSpec, TypeSpec};
+    /// use stargate_grpc::proto::type_spec::{Basic, Spec};
+    /// use stargate_grpc::result::ColumnType;
+    ///
+    /// let result_set = ResultSet {
+    ///     columns: vec![ColumnSpec {
+    ///         r#type: Some(TypeSpec { spec: Some(Spec::Basic(Basic::Bigint as i32)) }),
+    ///         name: "id".to_string(),
+    ///     }],
+    ///     rows: vec![],
+    ///     paging_state: None,
+    /// };
+    ///
+    /// assert_eq!(
+    ///     result_set.column_specs(),
+    ///     vec![("id".to_string(), ColumnType::Basic(Basic::Bigint))]
+    /// );
+    /// ```
+    pub fn column_specs(&self) -> Vec<(String, ColumnType)> {
+        self.columns
+            .iter()
+            .map(|c| (c.name.clone(), ColumnType::from(c.r#type.as_ref())))
+            .collect()
+    }
+
+    /// Returns the number of rows in this page of the result.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns true if this page of the result contains no rows.
+    ///
+    /// An empty page doesn't imply there are no more pages;
+    /// check [`has_more_pages`](ResultSet::has_more_pages) as well.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns an iterator over the rows, without consuming the result set.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::{ResultSet, Row, Value};
+    ///
+    /// let result_set = ResultSet {
+    ///     columns: vec![],
+    ///     rows: vec![Row { values: vec![Value::bigint(1)] }, Row { values: vec![Value::bigint(2)] }],
+    ///     paging_state: None,
+    /// };
+    ///
+    /// let mut sum = 0;
+    /// for row in &result_set {
+    ///     let id: i64 = row.try_get(0).unwrap();
+    ///     sum += id;
+    /// }
+    /// assert_eq!(sum, 3);
+    /// assert_eq!(result_set.iter().count(), 2);
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, Row> {
+        self.rows.iter()
+    }
+
+    /// Converts all rows, collecting the conversion failures instead of stopping at the first one.
+    ///
+    /// Returns the successfully converted rows and the failures, each paired with the
+    /// index of the row that failed to convert. Useful for processing as many rows as possible,
+    /// e.g. in data migration tools, and reporting the rest.
+    ///
+    /// # Example
+    /// ```
+    /// use stargate_grpc::{ResultSet, Row, Value};
+    ///
+    /// let result_set = ResultSet {
+    ///     columns: vec![],
+    ///     rows: vec![
+    ///         Row { values: vec![Value::bigint(1), Value::string("foo")] },
+    ///         Row { values: vec![Value::bigint(2), Value::bigint(3)] },
+    ///     ],
+    ///     paging_state: None,
+    /// };
+    ///
+    /// let (rows, errors) = result_set.try_map_rows_collecting::<(i64, String)>();
+    /// assert_eq!(rows, vec![(1, "foo".to_string())]);
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].0, 1);
+    /// ```
+    pub fn try_map_rows_collecting<T>(self) -> (Vec<T>, Vec<(usize, ConversionError)>)
+    where
+        T: TryFrom<Row, Error = ConversionError>,
+    {
+        let mut converted = Vec::with_capacity(self.rows.len());
+        let mut errors = Vec::new();
+        for (i, row) in self.rows.into_iter().enumerate() {
+            match T::try_from(row) {
+                Ok(value) => converted.push(value),
+                Err(error) => errors.push((i, error)),
+            }
+        }
+        (converted, errors)
+    }
+
     /// Returns a mapping from column names to column positions.
     /// The first column starts at position 0.
     fn column_positions(&self) -> HashMap<String, usize> {
@@ -255,3 +751,554 @@ impl ResultSet {
         result
     }
 }
+
+/// Consumes the result set and iterates over its rows.
+///
+/// # Example
+/// ```
+/// use std::convert::TryInto;
+/// use stargate_grpc::{ResultSet, Row, Value};
+///
+/// let result_set = ResultSet {
+///     columns: vec![],
+///     rows: vec![Row { values: vec![Value::bigint(1), Value::string("foo")] }],
+///     paging_state: None,
+/// };
+///
+/// assert_eq!(result_set.len(), 1);
+/// for row in result_set {
+///     let (id, login): (i64, String) = row.try_into().unwrap();
+///     assert_eq!(id, 1);
+///     assert_eq!(login, "foo");
+/// }
+/// ```
+impl IntoIterator for ResultSet {
+    type Item = Row;
+    type IntoIter = std::vec::IntoIter<Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ResultSet {
+    type Item = &'a Row;
+    type IntoIter = std::slice::Iter<'a, Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.iter()
+    }
+}
+
+/// CQL type of a column, decoded from the [`TypeSpec`] sent by the server.
+///
+/// Unlike `TypeSpec`, which stores enum values as raw integers and nested types
+/// as optional fields, `ColumnType` can be matched on directly.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnType {
+    /// A primitive type, e.g. `bigint` or `text`.
+    Basic(Basic),
+    List(Box<ColumnType>),
+    Set(Box<ColumnType>),
+    Map(Box<ColumnType>, Box<ColumnType>),
+    /// A user defined type with types of its fields, sorted by field name.
+    Udt(BTreeMap<String, ColumnType>),
+    Tuple(Vec<ColumnType>),
+    /// When the server didn't send the type or sent a type unknown to this client.
+    Unknown,
+}
+
+impl From<&TypeSpec> for ColumnType {
+    fn from(type_spec: &TypeSpec) -> Self {
+        let decode = |t: &Option<Box<TypeSpec>>| Box::new(ColumnType::from(t.as_deref()));
+        match &type_spec.spec {
+            Some(Spec::Basic(basic)) => Basic::from_i32(*basic)
+                .map(ColumnType::Basic)
+                .unwrap_or(ColumnType::Unknown),
+            Some(Spec::List(list)) => ColumnType::List(decode(&list.element)),
+            Some(Spec::Set(set)) => ColumnType::Set(decode(&set.element)),
+            Some(Spec::Map(map)) => ColumnType::Map(decode(&map.key), decode(&map.value)),
+            Some(Spec::Udt(udt)) => ColumnType::Udt(
+                udt.fields
+                    .iter()
+                    .map(|(name, t)| (name.clone(), ColumnType::from(t)))
+                    .collect(),
+            ),
+            Some(Spec::Tuple(tuple)) => {
+                ColumnType::Tuple(tuple.elements.iter().map(ColumnType::from).collect())
+            }
+            None => ColumnType::Unknown,
+        }
+    }
+}
+
+impl From<Option<&TypeSpec>> for ColumnType {
+    fn from(type_spec: Option<&TypeSpec>) -> Self {
+        type_spec
+            .map(ColumnType::from)
+            .unwrap_or(ColumnType::Unknown)
+    }
+}
+
+/// Error returned when a cell of a [`Table`] cannot be read.
+#[derive(Debug)]
+pub enum CellError {
+    /// When the row index is out of bounds.
+    RowNotFound(usize),
+    /// When the table has no column with the given name or index.
+    ColumnNotFound(String),
+    /// When the value of the cell could not be converted to the requested type.
+    Conversion(ConversionError),
+}
+
+impl From<ConversionError> for CellError {
+    fn from(error: ConversionError) -> Self {
+        CellError::Conversion(error)
+    }
+}
+
+impl Display for CellError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CellError::RowNotFound(index) => write!(f, "Row {} not found in the table", index),
+            CellError::ColumnNotFound(column) => {
+                write!(f, "Column {} not found in the table", column)
+            }
+            CellError::Conversion(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl Error for CellError {}
+
+/// Rows of a result set together with the specification of their columns.
+///
+/// A [`ResultSet`] keeps the column metadata separate from the rows, so a single
+/// [`Row`] doesn't know the names of its values. A `Table` holds both and allows to read
+/// any cell by the row index and the column name or index, converting it to the desired type.
+/// Cells are read by reference, so the same cell can be read many times.
+///
+/// Obtain a `Table` by converting a `ResultSet` or a query response with `try_into`.
+/// The paging state of the result set is not retained.
+/// Use [`ResultSet::mapper`] instead if you want to convert whole rows into structs.
+///
+/// # Example
+/// ```
+/// use stargate_grpc::{ResultSet, Row, Value};
+/// use stargate_grpc::proto::ColumnSpec;
+/// use stargate_grpc::result::Table;
+///
+/// fn column(name: &str) -> ColumnSpec {
+///     ColumnSpec {
+///         r#type: None,
+///         name: name.to_string(),
+///     }
+/// }
+/// let result_set = ResultSet {
+///     columns: vec![column("id"), column("login")],
+///     rows: vec![Row {
+///         values: vec![Value::bigint(1), Value::string("user_1")],
+///     }],
+///     paging_state: None,
+/// };
+///
+/// let table = Table::from(result_set);
+/// let login: String = table.get(0, "login").unwrap();
+/// assert_eq!(login, "user_1");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Table {
+    columns: Vec<ColumnSpec>,
+    rows: Vec<Row>,
+    column_positions: HashMap<String, usize>,
+}
+
+impl Table {
+    /// Returns the specification of the columns, in the order of values in the rows.
+    pub fn columns(&self) -> &[ColumnSpec] {
+        &self.columns
+    }
+
+    /// Returns the rows of the table.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Returns the position of the column with the given name.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.column_positions.get(name).copied()
+    }
+
+    /// Returns the raw value of the cell at the given row and column positions.
+    pub fn value(&self, row: usize, column: usize) -> Option<&Value> {
+        self.rows.get(row)?.values.get(column)
+    }
+
+    /// Returns a copy of the value of the cell in the given row and the column with
+    /// the given name, converted to the desired type.
+    ///
+    /// # Errors
+    /// Returns [`CellError`] if the row or column doesn't exist or if the conversion fails.
+    pub fn get<T: TryFromValue>(&self, row: usize, column: &str) -> Result<T, CellError> {
+        let index = self
+            .column_index(column)
+            .ok_or_else(|| CellError::ColumnNotFound(column.to_string()))?;
+        self.get_at(row, index)
+    }
+
+    /// Returns a copy of the value of the cell at the given row and column positions,
+    /// converted to the desired type.
+    ///
+    /// # Errors
+    /// Returns [`CellError`] if the row or column doesn't exist or if the conversion fails.
+    pub fn get_at<T: TryFromValue>(&self, row: usize, column: usize) -> Result<T, CellError> {
+        let row = self.rows.get(row).ok_or(CellError::RowNotFound(row))?;
+        let value = row
+            .values
+            .get(column)
+            .ok_or_else(|| CellError::ColumnNotFound(column.to_string()))?;
+        Ok(value.clone().try_into()?)
+    }
+}
+
+impl From<ResultSet> for Table {
+    fn from(result_set: ResultSet) -> Self {
+        Table {
+            column_positions: result_set.column_positions(),
+            columns: result_set.columns,
+            rows: result_set.rows,
+        }
+    }
+}
+
+impl TryFrom<tonic::Response<Response>> for Table {
+    type Error = ConversionError;
+
+    /// Converts a gRPC response received from the Stargate coordinator into a `Table`.
+    ///
+    /// Will return a `ConversionError` if the response does not contain a `ResultSet` message.
+    fn try_from(response: tonic::Response<Response>) -> Result<Self, Self::Error> {
+        Ok(ResultSet::try_from(response)?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::error::ConversionErrorKind;
+    use crate::proto::ColumnSpec;
+    use crate::result::{column_position_ignore_case, CellError, ColumnType, MapperError, Table};
+    use crate::{ResultSet, Row, Value};
+
+    fn result_set(column_names: &[&str]) -> ResultSet {
+        ResultSet {
+            columns: column_names
+                .iter()
+                .map(|name| ColumnSpec {
+                    r#type: None,
+                    name: name.to_string(),
+                })
+                .collect(),
+            rows: vec![],
+            paging_state: None,
+        }
+    }
+
+    #[test]
+    fn map_rows_collecting_errors() {
+        let mut rs = result_set(&["id", "login"]);
+        rs.rows = vec![
+            Row {
+                values: vec![Value::bigint(1), Value::string("a")],
+            },
+            Row {
+                values: vec![Value::string("2"), Value::string("b")],
+            },
+            Row {
+                values: vec![Value::bigint(3), Value::string("c")],
+            },
+            Row {
+                values: vec![Value::bigint(4)],
+            },
+        ];
+        let (rows, errors) = rs.try_map_rows_collecting::<(i64, String)>();
+        assert_eq!(rows, vec![(1, "a".to_string()), (3, "c".to_string())]);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].0, 1);
+        assert_eq!(errors[0].1.kind, ConversionErrorKind::Incompatible);
+        assert_eq!(errors[1].0, 3);
+        assert_eq!(
+            errors[1].1.kind,
+            ConversionErrorKind::WrongNumberOfItems {
+                actual: 1,
+                expected: 2
+            }
+        );
+    }
+
+    #[test]
+    fn project_columns() {
+        let mut rs = result_set(&["id", "login", "emails"]);
+        rs.rows = vec![
+            Row {
+                values: vec![
+                    Value::bigint(1),
+                    Value::string("a"),
+                    Value::list(vec!["a@example.net"]),
+                ],
+            },
+            Row {
+                values: vec![Value::bigint(2), Value::string("b"), Value::null()],
+            },
+        ];
+        rs.paging_state = Some(vec![1, 2]);
+
+        let projected = rs.project(&["emails", "id"]).unwrap();
+        assert!(projected.expect_columns(&["emails", "id"]).is_ok());
+        assert_eq!(
+            projected.rows,
+            vec![
+                Row {
+                    values: vec![Value::list(vec!["a@example.net"]), Value::bigint(1)],
+                },
+                Row {
+                    values: vec![Value::null(), Value::bigint(2)],
+                },
+            ]
+        );
+        assert_eq!(projected.paging_state, Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn project_repeated_column() {
+        let mut rs = result_set(&["id", "login"]);
+        rs.rows = vec![Row {
+            values: vec![Value::bigint(1), Value::string("a")],
+        }];
+
+        let projected = rs.project(&["login", "id", "login"]).unwrap();
+        assert!(projected.expect_columns(&["login", "id", "login"]).is_ok());
+        assert_eq!(
+            projected.rows[0].values,
+            vec![Value::string("a"), Value::bigint(1), Value::string("a")]
+        );
+    }
+
+    #[test]
+    fn project_unknown_column() {
+        let rs = result_set(&["id", "login"]);
+        match rs.project(&["id", "email"]) {
+            Err(MapperError::UnexpectedColumns { expected, actual }) => {
+                assert_eq!(expected, vec!["id", "email"]);
+                assert_eq!(actual, vec!["id", "login"]);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn expect_matching_columns() {
+        let rs = result_set(&["id", "login", "emails"]);
+        assert!(rs.expect_columns(&["id", "login", "emails"]).is_ok());
+    }
+
+    #[test]
+    fn expect_reordered_columns() {
+        let rs = result_set(&["id", "emails", "login"]);
+        match rs.expect_columns(&["id", "login", "emails"]) {
+            Err(MapperError::UnexpectedColumns { expected, actual }) => {
+                assert_eq!(expected, vec!["id", "login", "emails"]);
+                assert_eq!(actual, vec!["id", "emails", "login"]);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn expect_missing_columns() {
+        let rs = result_set(&["id", "login"]);
+        assert!(rs.expect_columns(&["id", "login", "emails"]).is_err());
+        assert!(rs.expect_columns(&["id"]).is_err());
+    }
+
+    #[test]
+    fn result_set_with_more_pages() {
+        let mut rs = result_set(&["id"]);
+        rs.paging_state = Some(vec![1, 2, 3]);
+        assert!(rs.has_more_pages());
+        assert_eq!(rs.take_paging_state(), Some(vec![1, 2, 3]));
+        assert!(!rs.has_more_pages());
+        assert_eq!(rs.take_paging_state(), None);
+    }
+
+    #[test]
+    fn result_set_without_more_pages() {
+        let mut rs = result_set(&["id"]);
+        assert!(!rs.has_more_pages());
+        assert_eq!(rs.take_paging_state(), None);
+
+        rs.paging_state = Some(vec![]);
+        assert!(!rs.has_more_pages());
+        assert_eq!(rs.take_paging_state(), None);
+    }
+
+    #[test]
+    fn find_column_ignoring_case() {
+        let mut positions = HashMap::new();
+        positions.insert("ID".to_string(), 0);
+        positions.insert("Login".to_string(), 1);
+        positions.insert("login".to_string(), 2);
+        positions.insert("LOGIN".to_string(), 3);
+        positions.insert("Email".to_string(), 4);
+        positions.insert("EMAIL".to_string(), 5);
+
+        assert_eq!(column_position_ignore_case(&positions, "id").unwrap(), 0);
+        assert_eq!(column_position_ignore_case(&positions, "login").unwrap(), 2);
+        assert!(matches!(
+            column_position_ignore_case(&positions, "email"),
+            Err(MapperError::AmbiguousColumn("email"))
+        ));
+        assert!(matches!(
+            column_position_ignore_case(&positions, "name"),
+            Err(MapperError::ColumnNotFound("name"))
+        ));
+    }
+
+    fn users_table() -> Table {
+        let mut rs = result_set(&["id", "login"]);
+        rs.rows = vec![
+            Row {
+                values: vec![Value::bigint(1), Value::string("user_1")],
+            },
+            Row {
+                values: vec![Value::bigint(2), Value::null()],
+            },
+        ];
+        Table::from(rs)
+    }
+
+    #[test]
+    fn get_table_cells_by_column_name() {
+        let table = users_table();
+        assert_eq!(table.get::<i64>(0, "id").unwrap(), 1);
+        assert_eq!(table.get::<String>(0, "login").unwrap(), "user_1");
+        assert_eq!(table.get::<i64>(1, "id").unwrap(), 2);
+        assert_eq!(table.get::<Option<String>>(1, "login").unwrap(), None);
+    }
+
+    #[test]
+    fn get_table_cells_by_column_index() {
+        let table = users_table();
+        assert_eq!(table.column_index("login"), Some(1));
+        assert_eq!(table.get_at::<String>(0, 1).unwrap(), "user_1");
+        assert_eq!(table.value(1, 0), Some(&Value::bigint(2)));
+        assert_eq!(table.value(2, 0), None);
+    }
+
+    #[test]
+    fn report_missing_table_cells() {
+        let table = users_table();
+        assert!(matches!(
+            table.get::<i64>(2, "id"),
+            Err(CellError::RowNotFound(2))
+        ));
+        assert!(matches!(
+            table.get::<i64>(0, "email"),
+            Err(CellError::ColumnNotFound(c)) if c == "email"
+        ));
+        assert!(matches!(
+            table.get_at::<i64>(0, 2),
+            Err(CellError::ColumnNotFound(c)) if c == "2"
+        ));
+        assert!(matches!(
+            table.get::<i64>(0, "login"),
+            Err(CellError::Conversion(_))
+        ));
+    }
+
+    #[test]
+    fn decode_column_specs() {
+        use crate::proto::type_spec::{self, Basic, Spec};
+        use crate::proto::TypeSpec;
+        use std::collections::BTreeMap;
+
+        fn basic(basic: Basic) -> TypeSpec {
+            TypeSpec {
+                spec: Some(Spec::Basic(basic as i32)),
+            }
+        }
+        fn boxed(basic_type: Basic) -> Option<Box<TypeSpec>> {
+            Some(Box::new(basic(basic_type)))
+        }
+
+        let mut rs = result_set(&["id", "tags", "scores", "address", "point", "custom"]);
+        let types = vec![
+            Some(basic(Basic::Uuid)),
+            Some(TypeSpec {
+                spec: Some(Spec::Set(Box::new(type_spec::Set {
+                    element: boxed(Basic::Varchar),
+                }))),
+            }),
+            Some(TypeSpec {
+                spec: Some(Spec::Map(Box::new(type_spec::Map {
+                    key: boxed(Basic::Text),
+                    value: Some(Box::new(TypeSpec {
+                        spec: Some(Spec::List(Box::new(type_spec::List {
+                            element: boxed(Basic::Double),
+                        }))),
+                    })),
+                }))),
+            }),
+            Some(TypeSpec {
+                spec: Some(Spec::Udt(type_spec::Udt {
+                    fields: vec![
+                        ("street".to_string(), basic(Basic::Text)),
+                        ("number".to_string(), basic(Basic::Int)),
+                    ]
+                    .into_iter()
+                    .collect(),
+                })),
+            }),
+            Some(TypeSpec {
+                spec: Some(Spec::Tuple(type_spec::Tuple {
+                    elements: vec![basic(Basic::Float), basic(Basic::Float)],
+                })),
+            }),
+            None,
+        ];
+        for (column, t) in rs.columns.iter_mut().zip(types) {
+            column.r#type = t;
+        }
+
+        let mut address = BTreeMap::new();
+        address.insert("number".to_string(), ColumnType::Basic(Basic::Int));
+        address.insert("street".to_string(), ColumnType::Basic(Basic::Text));
+        assert_eq!(
+            rs.column_specs(),
+            vec![
+                ("id".to_string(), ColumnType::Basic(Basic::Uuid)),
+                (
+                    "tags".to_string(),
+                    ColumnType::Set(Box::new(ColumnType::Basic(Basic::Varchar)))
+                ),
+                (
+                    "scores".to_string(),
+                    ColumnType::Map(
+                        Box::new(ColumnType::Basic(Basic::Text)),
+                        Box::new(ColumnType::List(Box::new(ColumnType::Basic(Basic::Double))))
+                    )
+                ),
+                ("address".to_string(), ColumnType::Udt(address)),
+                (
+                    "point".to_string(),
+                    ColumnType::Tuple(vec![
+                        ColumnType::Basic(Basic::Float),
+                        ColumnType::Basic(Basic::Float)
+                    ])
+                ),
+                ("custom".to_string(), ColumnType::Unknown),
+            ]
+        );
+    }
+}
@@ -60,6 +60,9 @@ impl ConcreteType for Time {}
 pub struct Timestamp;
 impl ConcreteType for Timestamp {}
 
+pub struct Timeuuid;
+impl ConcreteType for Timeuuid {}
+
 pub struct Tinyint;
 impl ConcreteType for Tinyint {}
 
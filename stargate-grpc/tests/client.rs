@@ -0,0 +1,447 @@
+//! Tests of the client against a mock Stargate server running in-process.
+
+// The mock returns `tonic::Status` errors, like the real service:
+#![allow(clippy::result_large_err)]
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Status};
+
+use stargate_grpc::client::AuthToken;
+use stargate_grpc::proto::stargate_server::{Stargate, StargateServer};
+use stargate_grpc::proto::{response, Batch, BatchParameters, Response};
+use stargate_grpc::{Query, ResultSet, Row, StargateClient, Value};
+
+type Responder = Box<dyn Fn(&Query) -> Result<Response, Status> + Send + Sync>;
+
+/// Records received queries and answers them with responses produced by the responder.
+/// Queries without an authentication token are rejected.
+/// Batches are recorded separately and answered with an empty result.
+struct MockStargate {
+    queries: Arc<Mutex<Vec<Query>>>,
+    batches: Arc<Mutex<Vec<Batch>>>,
+    responder: Responder,
+}
+
+#[tonic::async_trait]
+impl Stargate for MockStargate {
+    async fn execute_query(
+        &self,
+        request: Request<Query>,
+    ) -> Result<tonic::Response<Response>, Status> {
+        if request.metadata().get("x-cassandra-token").is_none() {
+            return Err(Status::unauthenticated("missing token"));
+        }
+        let query = request.into_inner();
+        let response = (self.responder)(&query);
+        self.queries.lock().unwrap().push(query);
+        response.map(tonic::Response::new)
+    }
+
+    async fn execute_batch(
+        &self,
+        request: Request<Batch>,
+    ) -> Result<tonic::Response<Response>, Status> {
+        self.batches.lock().unwrap().push(request.into_inner());
+        Ok(tonic::Response::new(empty_result()))
+    }
+}
+
+/// Returns a response with an empty result set.
+fn empty_result() -> Response {
+    Response {
+        result: Some(response::Result::ResultSet(ResultSet::default())),
+        ..Default::default()
+    }
+}
+
+/// Starts the mock server on a random local port.
+/// Returns the address of the server, the list of queries received by the server
+/// and the number of connections accepted by the server.
+async fn start_server(
+    responder: Responder,
+) -> (SocketAddr, Arc<Mutex<Vec<Query>>>, Arc<AtomicUsize>) {
+    let queries = Arc::new(Mutex::new(Vec::new()));
+    let mock = MockStargate {
+        queries: queries.clone(),
+        batches: Default::default(),
+        responder,
+    };
+    let (address, connections) = serve(mock).await;
+    (address, queries, connections)
+}
+
+/// Serves the mock on a random local port.
+/// Returns the address of the server and the number of connections accepted by the server.
+async fn serve(mock: MockStargate) -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    let incoming = TcpListenerStream::new(listener).map(move |stream| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        stream
+    });
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(StargateServer::new(mock))
+            .serve_with_incoming(incoming),
+    );
+    (address, connections)
+}
+
+/// Connects a client to the server at given address.
+async fn connect(address: SocketAddr) -> StargateClient {
+    StargateClient::builder()
+        .uri(format!("http://{}", address))
+        .unwrap()
+        .auth_token(AuthToken::from_str("token").unwrap())
+        .connect()
+        .await
+        .unwrap()
+}
+
+/// Starts the mock server on a random local port and connects a client to it.
+/// Returns the client and the list of queries received by the server.
+async fn start_mock(responder: Responder) -> (StargateClient, Arc<Mutex<Vec<Query>>>) {
+    let (address, queries, _) = start_server(responder).await;
+    (connect(address).await, queries)
+}
+
+/// Starts the mock server on a random local port and connects a client to it.
+/// Returns the client and the list of batches received by the server.
+async fn start_batch_mock() -> (StargateClient, Arc<Mutex<Vec<Batch>>>) {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let mock = MockStargate {
+        queries: Default::default(),
+        batches: batches.clone(),
+        responder: Box::new(|_| Ok(empty_result())),
+    };
+    let (address, _) = serve(mock).await;
+    (connect(address).await, batches)
+}
+
+#[tokio::test]
+async fn execute_the_same_query_twice() {
+    let (mut client, queries) = start_mock(Box::new(|_| Ok(empty_result()))).await;
+    let query = Query::builder()
+        .keyspace("ks")
+        .query("SELECT * FROM users WHERE id = ?")
+        .bind((1,))
+        .build();
+
+    client.execute_query_ref(&query).await.unwrap();
+    client.execute_query_ref(&query).await.unwrap();
+
+    let queries = queries.lock().unwrap();
+    assert_eq!(*queries, vec![query.clone(), query]);
+}
+
+#[tokio::test]
+async fn connect_with_pre_built_channel() {
+    let (address, queries, _) = start_server(Box::new(|_| Ok(empty_result()))).await;
+    let channel = tonic::transport::Endpoint::from_shared(format!("http://{}", address))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = StargateClient::builder()
+        .channel(channel)
+        .auth_token(AuthToken::from_str("token").unwrap())
+        .connect()
+        .await
+        .unwrap();
+
+    let query = Query::builder().query("SELECT * FROM users").build();
+    client.execute_query(query.clone()).await.unwrap();
+    assert_eq!(*queries.lock().unwrap(), vec![query]);
+}
+
+#[tokio::test]
+async fn execute_query_with_deadline() {
+    let (mut client, queries) = start_mock(Box::new(|_| Ok(empty_result()))).await;
+    let query = Query::builder().query("SELECT * FROM users").build();
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    client
+        .execute_query_with_deadline(query, deadline)
+        .await
+        .unwrap();
+    assert_eq!(queries.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn fail_query_with_expired_deadline_without_sending_it() {
+    let (mut client, queries) = start_mock(Box::new(|_| Ok(empty_result()))).await;
+    let query = Query::builder().query("SELECT * FROM users").build();
+
+    let deadline = tokio::time::Instant::now() - Duration::from_millis(1);
+    let status = client
+        .execute_query_with_deadline(query, deadline)
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+    assert!(queries.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn execute_batch_of_generated_queries() {
+    let (mut client, batches) = start_batch_mock().await;
+    let queries = (1..=3).map(|id| {
+        Query::builder()
+            .keyspace("ignored")
+            .query("INSERT INTO users (id) VALUES (?)")
+            .bind((id,))
+            .build()
+    });
+    let parameters = BatchParameters {
+        keyspace: Some("ks".to_string()),
+        ..Default::default()
+    };
+
+    client.execute_batch_of(queries, parameters).await.unwrap();
+
+    let batches = batches.lock().unwrap();
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!(batch.queries.len(), 3);
+    for (query, id) in batch.queries.iter().zip(1..) {
+        assert_eq!(query.cql, "INSERT INTO users (id) VALUES (?)");
+        assert_eq!(
+            query.values.as_ref().unwrap().values,
+            vec![Value::bigint(id)]
+        );
+    }
+    assert_eq!(
+        batch.parameters.as_ref().unwrap().keyspace,
+        Some("ks".to_string())
+    );
+}
+
+#[tokio::test]
+async fn retry_only_idempotent_statements() {
+    let failures = Arc::new(AtomicUsize::new(0));
+    let remaining = failures.clone();
+    let (mut client, queries) = start_mock(Box::new(move |_| {
+        let fail = remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if fail {
+            Err(Status::unavailable("coordinator unavailable"))
+        } else {
+            Ok(empty_result())
+        }
+    }))
+    .await;
+
+    failures.store(2, Ordering::SeqCst);
+    let statement = Query::builder()
+        .query("UPDATE users SET login = 'admin' WHERE id = 1")
+        .idempotent(true)
+        .build_statement();
+    client.execute_statement(&statement, 3).await.unwrap();
+    assert_eq!(queries.lock().unwrap().len(), 3);
+
+    queries.lock().unwrap().clear();
+    failures.store(2, Ordering::SeqCst);
+    let statement = Query::builder()
+        .query("INSERT INTO users (id) VALUES (1)")
+        .build_statement();
+    let status = client.execute_statement(&statement, 3).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+    assert_eq!(queries.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn balance_queries_across_coordinators() {
+    let (address1, queries1, _) = start_server(Box::new(|_| Ok(empty_result()))).await;
+    let (address2, queries2, _) = start_server(Box::new(|_| Ok(empty_result()))).await;
+    let mut client = StargateClient::builder()
+        .uris(vec![
+            format!("http://{}", address1).parse().unwrap(),
+            format!("http://{}", address2).parse().unwrap(),
+        ])
+        .auth_token(AuthToken::from_str("token").unwrap())
+        .connect()
+        .await
+        .unwrap();
+
+    let query = Query::builder().query("SELECT * FROM users").build();
+    for _ in 0..50 {
+        client.execute_query_ref(&query).await.unwrap();
+    }
+
+    let count1 = queries1.lock().unwrap().len();
+    let count2 = queries2.lock().unwrap().len();
+    assert_eq!(count1 + count2, 50);
+    assert!(count1 > 0, "no queries sent to the first coordinator");
+    assert!(count2 > 0, "no queries sent to the second coordinator");
+}
+
+/// Returns an address nothing listens at, by binding a port and releasing it.
+fn unreachable_address() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn skip_unreachable_coordinators() {
+    let (address, queries, _) = start_server(Box::new(|_| Ok(empty_result()))).await;
+    let mut client = StargateClient::builder()
+        .uris(vec![
+            format!("http://{}", unreachable_address()).parse().unwrap(),
+            format!("http://{}", address).parse().unwrap(),
+        ])
+        .auth_token(AuthToken::from_str("token").unwrap())
+        .connect()
+        .await
+        .unwrap();
+
+    let query = Query::builder().query("SELECT * FROM users").build();
+    for _ in 0..10 {
+        client.execute_query_ref(&query).await.unwrap();
+    }
+    assert_eq!(queries.lock().unwrap().len(), 10);
+}
+
+#[tokio::test]
+async fn fail_to_connect_if_no_coordinator_is_reachable() {
+    let result = StargateClient::builder()
+        .uris(vec![
+            format!("http://{}", unreachable_address()).parse().unwrap(),
+            format!("http://{}", unreachable_address()).parse().unwrap(),
+        ])
+        .auth_token(AuthToken::from_str("token").unwrap())
+        .connect()
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn reconnect_after_repeated_unavailable_errors() {
+    let failures = AtomicUsize::new(0);
+    let (address, queries, connections) = start_server(Box::new(move |_| {
+        if failures.fetch_add(1, Ordering::SeqCst) < 2 {
+            Err(Status::unavailable("coordinator unavailable"))
+        } else {
+            Ok(empty_result())
+        }
+    }))
+    .await;
+    let mut client = StargateClient::builder()
+        .uri(format!("http://{}", address))
+        .unwrap()
+        .auth_token(AuthToken::from_str("token").unwrap())
+        .connect_reconnecting(2)
+        .await
+        .unwrap();
+
+    let query = Query::builder().query("SELECT * FROM users").build();
+    let error = client.execute_query(query.clone()).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::Unavailable);
+    assert_eq!(client.reconnect_count(), 0);
+    let error = client.execute_query(query.clone()).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::Unavailable);
+    assert_eq!(client.reconnect_count(), 1);
+    client.execute_query(query).await.unwrap();
+
+    assert_eq!(queries.lock().unwrap().len(), 3);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn serialize_requests_under_concurrency_limit() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let (in_flight_copy, max_in_flight_copy) = (in_flight.clone(), max_in_flight.clone());
+    let (address, queries, _) = start_server(Box::new(move |_| {
+        let count = in_flight_copy.fetch_add(1, Ordering::SeqCst) + 1;
+        max_in_flight_copy.fetch_max(count, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(50));
+        in_flight_copy.fetch_sub(1, Ordering::SeqCst);
+        Ok(empty_result())
+    }))
+    .await;
+    let client = StargateClient::builder()
+        .uri(format!("http://{}", address))
+        .unwrap()
+        .auth_token(AuthToken::from_str("token").unwrap())
+        .concurrency_limit(1)
+        .connect()
+        .await
+        .unwrap();
+
+    let query = Query::builder().query("SELECT * FROM users").build();
+    let requests = (0..4).map(|_| {
+        let mut client = client.clone();
+        let query = query.clone();
+        tokio::spawn(async move { client.execute_query(query).await })
+    });
+    for result in futures_util::future::join_all(requests).await {
+        result.unwrap().unwrap();
+    }
+
+    assert_eq!(queries.lock().unwrap().len(), 4);
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn query_page_returns_rows_and_paging_state() {
+    let (mut client, queries) = start_mock(Box::new(|query| {
+        let paging_state = query
+            .parameters
+            .as_ref()
+            .and_then(|p| p.paging_state.clone());
+        let (first, next_page) = match paging_state {
+            None => (1, Some(vec![1, 2, 3])),
+            Some(_) => (3, None),
+        };
+        let result_set = ResultSet {
+            columns: vec![],
+            rows: (first..first + 2)
+                .map(|i| Row {
+                    values: vec![Value::bigint(i), Value::string(format!("user_{}", i))],
+                })
+                .collect(),
+            paging_state: next_page,
+        };
+        Ok(Response {
+            result: Some(response::Result::ResultSet(result_set)),
+            ..Default::default()
+        })
+    }))
+    .await;
+
+    let query = Query::builder()
+        .query("SELECT id, login FROM users")
+        .page_size(2);
+    let (users, paging_state): (Vec<(i64, String)>, _) =
+        client.query_page(query.clone().build()).await.unwrap();
+    assert_eq!(
+        users,
+        vec![(1, "user_1".to_string()), (2, "user_2".to_string())]
+    );
+    assert_eq!(paging_state, Some(vec![1, 2, 3]));
+
+    let query = query.paging_state(paging_state.unwrap()).build();
+    let (users, paging_state): (Vec<(i64, String)>, _) = client.query_page(query).await.unwrap();
+    assert_eq!(
+        users,
+        vec![(3, "user_3".to_string()), (4, "user_4".to_string())]
+    );
+    assert_eq!(paging_state, None);
+
+    let queries = queries.lock().unwrap();
+    assert_eq!(
+        queries[1].parameters.as_ref().unwrap().paging_state,
+        Some(vec![1, 2, 3])
+    );
+}
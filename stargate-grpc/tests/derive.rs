@@ -103,6 +103,205 @@ mod derive {
         assert_eq!(addr.number, 123);
     }
 
+    #[test]
+    fn rename_fields_with_rename_alias() {
+        #[derive(Eq, PartialEq, IntoValue, TryFromValue)]
+        struct Address {
+            #[stargate(rename = "st")]
+            street: String,
+            number: i64,
+        }
+        let addr = Address {
+            street: "foo".to_string(),
+            number: 123,
+        };
+        let value = Value::from(addr);
+        match &value.inner {
+            Some(stargate_grpc::proto::value::Inner::Udt(value)) => {
+                assert_eq!(value.fields.get("st"), Some(&Value::string("foo")));
+                assert_eq!(value.fields.get("street"), None);
+            }
+            inner => panic!("Unexpected udt inner value {:?}", inner),
+        }
+        let addr: Address = value.try_into().unwrap();
+        assert_eq!(addr.street, "foo".to_string());
+    }
+
+    #[test]
+    fn convert_struct_as_map_round_trip() {
+        #[derive(Debug, Eq, PartialEq, IntoValue, TryFromValue)]
+        #[stargate(as_map)]
+        struct Labels {
+            color: String,
+            #[stargate(name = "sz")]
+            size: String,
+        }
+        let labels = Labels {
+            color: "red".to_string(),
+            size: "XL".to_string(),
+        };
+        let value = Value::from(labels);
+        assert_eq!(
+            value,
+            Value::of_type(
+                types::Map(types::Text, types::Text),
+                vec![("color", "red"), ("sz", "XL")]
+            )
+        );
+
+        // read back from a map<text, text> column:
+        let row = Row {
+            values: vec![Value::bigint(1), value],
+        };
+        let labels: Labels = row.try_get(1).unwrap();
+        assert_eq!(
+            labels,
+            Labels {
+                color: "red".to_string(),
+                size: "XL".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn convert_map_with_unknown_entries_to_struct_as_map_with_flatten_map() {
+        #[derive(Debug, IntoValue, TryFromValue)]
+        #[stargate(as_map)]
+        struct Labels {
+            color: String,
+            #[stargate(flatten_map)]
+            other: HashMap<String, Value>,
+        }
+        let value = Value::map(vec![("color", "red"), ("fit", "slim")]);
+        let labels: Labels = value.try_into().unwrap();
+        assert_eq!(labels.color, "red");
+        assert_eq!(labels.other.len(), 1);
+        assert_eq!(labels.other["fit"], Value::string("slim"));
+
+        // unknown entries are written back, mapped fields take precedence:
+        let mut other = labels.other;
+        other.insert("color".to_string(), Value::string("blue"));
+        let labels = Labels {
+            color: "red".to_string(),
+            other,
+        };
+        let entries: HashMap<String, String> = Value::from(labels).try_into().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries["color"], "red");
+        assert_eq!(entries["fit"], "slim");
+    }
+
+    #[test]
+    fn convert_udt_value_to_struct_as_map_fails() {
+        #[derive(Debug, TryFromValue)]
+        #[stargate(as_map)]
+        struct Labels {
+            #[allow(dead_code)]
+            color: String,
+        }
+        let value = Value::udt(vec![("color", "red")]);
+        let result: Result<Labels, ConversionError> = value.try_into();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn convert_udt_value_with_unknown_fields_to_flatten_map() {
+        #[derive(Debug, IntoValue, TryFromValue)]
+        struct User {
+            id: i64,
+            #[stargate(flatten_map)]
+            other: HashMap<String, Value>,
+            login: String,
+        }
+        let value = Value::udt(vec![
+            ("id", Value::bigint(1)),
+            ("login", Value::string("user")),
+            ("email", Value::string("user@example.com")),
+            ("age", Value::bigint(30)),
+        ]);
+        let user: User = value.clone().try_into().unwrap();
+        assert_eq!(user.id, 1);
+        assert_eq!(user.login, "user");
+        assert_eq!(user.other.len(), 2);
+        assert_eq!(user.other["email"], Value::string("user@example.com"));
+        assert_eq!(user.other["age"], Value::bigint(30));
+
+        // unknown fields are written back:
+        assert_eq!(Value::from(user), value);
+
+        // mapped fields take precedence over the same fields in the catch-all:
+        let mut other = HashMap::new();
+        other.insert("id".to_string(), Value::bigint(2));
+        let user = User {
+            id: 1,
+            other,
+            login: "user".to_string(),
+        };
+        assert_eq!(
+            Value::from(user),
+            Value::udt(vec![
+                ("id", Value::bigint(1)),
+                ("login", Value::string("user"))
+            ])
+        );
+    }
+
+    #[derive(Debug, Eq, PartialEq, IntoValue, TryFromValue)]
+    struct UserId(i64);
+
+    #[derive(Debug, Eq, PartialEq, IntoValue, TryFromValue)]
+    struct Login(String);
+
+    #[test]
+    fn convert_newtype_round_trip() {
+        let value = Value::from(UserId(7));
+        assert_eq!(value, Value::bigint(7));
+        let id: UserId = value.try_into().unwrap();
+        assert_eq!(id, UserId(7));
+
+        let value = Value::from(Login("user".to_string()));
+        assert_eq!(value, Value::string("user"));
+        let login: Login = value.try_into().unwrap();
+        assert_eq!(login, Login("user".to_string()));
+    }
+
+    #[test]
+    fn convert_newtype_fields_and_collections() {
+        #[derive(Debug, Eq, PartialEq, IntoValue, TryFromValue)]
+        struct Email(#[stargate(cql_type = "types::Text")] String);
+
+        #[derive(IntoValue, TryFromValue)]
+        struct User {
+            id: UserId,
+            login: Login,
+            email: Option<Email>,
+        }
+        let user = User {
+            id: UserId(1),
+            login: Login("user".to_string()),
+            email: Some(Email("user@example.com".to_string())),
+        };
+        let value = Value::from(user);
+        let user: User = value.try_into().unwrap();
+        assert_eq!(user.id, UserId(1));
+        assert_eq!(user.login, Login("user".to_string()));
+        assert_eq!(user.email, Some(Email("user@example.com".to_string())));
+
+        let ids = Value::list(vec![UserId(1), UserId(2)]);
+        let ids: Vec<UserId> = ids.try_into().unwrap();
+        assert_eq!(ids, vec![UserId(1), UserId(2)]);
+    }
+
+    #[test]
+    fn convert_newtype_with_cql_type() {
+        #[derive(IntoValue)]
+        struct Address(#[stargate(cql_type = "types::Inet")] [u8; 4]);
+        assert_eq!(
+            Value::from(Address([127, 0, 0, 1])),
+            Value::inet([127, 0, 0, 1])
+        );
+    }
+
     #[test]
     fn convert_udt_value_to_struct() {
         #[derive(TryFromValue)]
@@ -144,6 +343,28 @@ mod derive {
         assert_eq!(file.write_lock, true);
     }
 
+    #[test]
+    fn convert_udt_value_to_struct_with_default_all() {
+        #[derive(TryFromValue)]
+        #[stargate(default_all)]
+        struct Profile {
+            login: String,
+            age: i64,
+            tags: Vec<String>,
+            #[stargate(default = "\"en\".to_string()")]
+            language: String,
+        }
+        let value = Value::udt(vec![
+            ("login", Value::string("user")),
+            ("age", Value::null()),
+        ]);
+        let profile: Profile = value.try_into().unwrap();
+        assert_eq!(profile.login, "user");
+        assert_eq!(profile.age, 0);
+        assert!(profile.tags.is_empty());
+        assert_eq!(profile.language, "en");
+    }
+
     #[test]
     fn convert_udt_value_to_struct_returns_err_on_field_conversion_err() {
         #[derive(TryFromValue)]
@@ -199,7 +420,7 @@ mod derive {
 
         use prost::Message;
         let values: proto::Values =
-            proto::Values::decode(query.values.unwrap().data.unwrap().value.as_slice()).unwrap();
+            proto::Values::decode(query.values.unwrap().encode_to_vec().as_slice()).unwrap();
         assert_eq!(
             values.value_names,
             vec!["id".to_string(), "login".to_string()]
@@ -207,6 +428,29 @@ mod derive {
         assert_eq!(values.values, vec![Value::bigint(1), Value::string("user")]);
     }
 
+    #[test]
+    fn bind_struct_with_narrow_int_fields_in_query() {
+        #[derive(IntoValues)]
+        struct Counters {
+            tiny: i8,
+            small: i16,
+        }
+        let counters = Counters {
+            tiny: -128,
+            small: 32767,
+        };
+        let query = Query::builder()
+            .query("INSERT INTO counters(tiny, small) VALUES (:tiny, :small)")
+            .bind(counters)
+            .build();
+
+        let values = query.values.unwrap();
+        assert_eq!(
+            values.values,
+            vec![Value::tinyint(-128_i8), Value::smallint(32767_i16)]
+        );
+    }
+
     #[test]
     fn get_column_positions() {
         #[derive(TryFromRow)]
@@ -268,6 +512,333 @@ mod derive {
         }
     }
 
+    #[test]
+    fn convert_result_set_into_typed_rows() {
+        #[derive(Debug, PartialEq, TryFromRow)]
+        struct User {
+            id: i64,
+            login: String,
+        }
+        let result_set = ResultSet {
+            columns: vec![column("login"), column("id")],
+            rows: vec![
+                Row {
+                    values: vec![Value::string("user_1"), Value::bigint(1)],
+                },
+                Row {
+                    values: vec![Value::string("user_2"), Value::bigint(2)],
+                },
+            ],
+            paging_state: None,
+        };
+
+        let users: Result<Vec<User>, ConversionError> =
+            result_set.clone().into_typed().unwrap().collect();
+        assert_eq!(
+            users.unwrap(),
+            vec![
+                User {
+                    id: 1,
+                    login: "user_1".to_string()
+                },
+                User {
+                    id: 2,
+                    login: "user_2".to_string()
+                }
+            ]
+        );
+
+        let mut invalid = result_set;
+        invalid.rows[1].values[1] = Value::string("two");
+        let users: Result<Vec<User>, ConversionError> = invalid.into_typed().unwrap().collect();
+        assert!(users.unwrap_err().is_incompatible());
+    }
+
+    #[test]
+    fn convert_row_to_struct_with_flattened_struct() {
+        #[derive(Debug, PartialEq, TryFromRow)]
+        struct Audit {
+            created_by: String,
+            #[stargate(name = "modified")]
+            modified_at: i64,
+        }
+        #[derive(TryFromRow)]
+        struct Document {
+            id: i64,
+            #[stargate(flatten)]
+            audit: Audit,
+            title: String,
+        }
+        let result_set = ResultSet {
+            columns: vec![
+                column("title"),
+                column("modified"),
+                column("id"),
+                column("created_by"),
+            ],
+            rows: vec![Row {
+                values: vec![
+                    Value::string("Report"),
+                    Value::bigint(1000),
+                    Value::bigint(1),
+                    Value::string("admin"),
+                ],
+            }],
+            paging_state: None,
+        };
+
+        let mapper = result_set.mapper().unwrap();
+        for row in result_set.rows {
+            let document: Document = mapper.try_unpack(row).unwrap();
+            assert_eq!(document.id, 1);
+            assert_eq!(document.title, "Report");
+            assert_eq!(
+                document.audit,
+                Audit {
+                    created_by: "admin".to_string(),
+                    modified_at: 1000,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn get_column_names() {
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Audit {
+            created_by: String,
+            #[stargate(name = "modified")]
+            modified_at: i64,
+        }
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Document {
+            id: i64,
+            #[stargate(rename = "document_title")]
+            title: String,
+            #[stargate(flatten)]
+            audit: Audit,
+        }
+        use stargate_grpc::result::ColumnPositions;
+        assert_eq!(Audit::column_names(), &["created_by", "modified"]);
+        assert_eq!(
+            Document::column_names(),
+            &["id", "document_title", "created_by", "modified"]
+        );
+    }
+
+    #[test]
+    fn get_column_names_of_flattened_manual_impl() {
+        use stargate_grpc::result::{ColumnPositions, MapperError, TryFromRow};
+
+        #[allow(unused)]
+        struct Audit(String);
+        impl ColumnPositions for Audit {
+            fn field_to_column_pos(
+                column_positions: HashMap<String, usize>,
+            ) -> Result<Vec<usize>, MapperError> {
+                let pos = column_positions
+                    .get("created_by")
+                    .ok_or(MapperError::ColumnNotFound("created_by"))?;
+                Ok(vec![*pos])
+            }
+            fn column_names() -> &'static [&'static str] {
+                &["created_by"]
+            }
+        }
+        impl TryFromRow for Audit {
+            fn try_unpack(
+                mut row: Row,
+                column_positions: &[usize],
+            ) -> Result<Self, ConversionError> {
+                Ok(Audit(row.values[column_positions[0]].take().try_into()?))
+            }
+        }
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Document {
+            id: i64,
+            #[stargate(flatten)]
+            audit: Audit,
+        }
+        assert_eq!(Document::column_names(), &["id", "created_by"]);
+    }
+
+    #[test]
+    fn convert_row_to_struct_with_flattened_manual_impl() {
+        use stargate_grpc::result::{ColumnPositions, MapperError, TryFromRow};
+
+        #[derive(Debug, PartialEq)]
+        struct Audit(String);
+        impl ColumnPositions for Audit {
+            fn field_to_column_pos(
+                column_positions: HashMap<String, usize>,
+            ) -> Result<Vec<usize>, MapperError> {
+                let pos = column_positions
+                    .get("created_by")
+                    .ok_or(MapperError::ColumnNotFound("created_by"))?;
+                Ok(vec![*pos])
+            }
+            fn column_names() -> &'static [&'static str] {
+                &["created_by"]
+            }
+        }
+        impl TryFromRow for Audit {
+            fn try_unpack(
+                mut row: Row,
+                column_positions: &[usize],
+            ) -> Result<Self, ConversionError> {
+                Ok(Audit(row.values[column_positions[0]].take().try_into()?))
+            }
+        }
+        #[derive(Debug, TryFromRow)]
+        struct Document {
+            id: i64,
+            #[stargate(flatten)]
+            audit: Audit,
+            title: String,
+        }
+        let result_set = ResultSet {
+            columns: vec![column("title"), column("created_by"), column("id")],
+            rows: vec![Row {
+                values: vec![
+                    Value::string("Report"),
+                    Value::string("admin"),
+                    Value::bigint(1),
+                ],
+            }],
+            paging_state: None,
+        };
+        let mapper = result_set.mapper().unwrap();
+        let document: Document = mapper
+            .try_unpack(result_set.rows.into_iter().next().unwrap())
+            .unwrap();
+        assert_eq!(document.id, 1);
+        assert_eq!(document.audit, Audit("admin".to_string()));
+        assert_eq!(document.title, "Report");
+    }
+
+    #[test]
+    fn reject_flattened_manual_impl_with_wrong_column_names() {
+        use stargate_grpc::result::{ColumnPositions, MapperError, TryFromRow};
+
+        struct Audit;
+        impl ColumnPositions for Audit {
+            fn field_to_column_pos(
+                column_positions: HashMap<String, usize>,
+            ) -> Result<Vec<usize>, MapperError> {
+                let created_by = column_positions["created_by"];
+                let modified = column_positions["modified"];
+                Ok(vec![created_by, modified])
+            }
+            fn column_names() -> &'static [&'static str] {
+                &["created_by"]
+            }
+        }
+        impl TryFromRow for Audit {
+            fn try_unpack(_row: Row, _column_positions: &[usize]) -> Result<Self, ConversionError> {
+                Ok(Audit)
+            }
+        }
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Document {
+            id: i64,
+            #[stargate(flatten)]
+            audit: Audit,
+        }
+        let result_set = ResultSet {
+            columns: vec![column("id"), column("created_by"), column("modified")],
+            rows: vec![],
+            paging_state: None,
+        };
+        match result_set.mapper::<Document>() {
+            Err(MapperError::ColumnNamesMismatch {
+                names, positions, ..
+            }) => {
+                assert_eq!(names, 1);
+                assert_eq!(positions, 2);
+            }
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Expected an error"),
+        }
+    }
+
+    #[test]
+    fn reject_column_read_by_outer_and_flattened_struct() {
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Audit {
+            id: i64,
+            created_by: String,
+        }
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Document {
+            id: i64,
+            #[stargate(flatten)]
+            audit: Audit,
+        }
+        let result_set = ResultSet {
+            columns: vec![column("id"), column("created_by")],
+            rows: vec![],
+            paging_state: None,
+        };
+        use stargate_grpc::result::MapperError;
+        match result_set.mapper::<Document>() {
+            Err(MapperError::DuplicateColumn(name)) => assert_eq!(name, "id"),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Expected an error"),
+        }
+    }
+
+    #[test]
+    fn get_column_positions_missing_column_of_flattened_struct() {
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Audit {
+            created_by: String,
+        }
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Document {
+            id: i64,
+            #[stargate(flatten)]
+            audit: Audit,
+        }
+        use stargate_grpc::result::{ColumnPositions, MapperError};
+        let mut positions = HashMap::new();
+        positions.insert("id".to_string(), 6);
+        match Document::field_to_column_pos(positions) {
+            Err(MapperError::ColumnNotFound(name)) => assert_eq!(name, "created_by"),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn convert_row_to_struct_with_case_insensitive_columns() {
+        #[derive(Debug, TryFromRow)]
+        #[stargate(case_insensitive)]
+        struct User {
+            id: i64,
+            login: String,
+        }
+        let result_set = ResultSet {
+            columns: vec![column("LOGIN"), column("Id")],
+            rows: vec![Row {
+                values: vec![Value::string("user"), Value::bigint(1)],
+            }],
+            paging_state: None,
+        };
+        let mapper = result_set.mapper().unwrap();
+        let user: User = mapper
+            .try_unpack(result_set.rows.into_iter().next().unwrap())
+            .unwrap();
+        assert_eq!(user.id, 1);
+        assert_eq!(user.login, "user");
+    }
+
     #[test]
     fn convert_row_to_struct_returns_err_on_missing_column() {
         #[derive(TryFromRow)]
@@ -313,4 +884,148 @@ mod derive {
             assert!(user.is_err());
         }
     }
+
+    #[test]
+    fn convert_row_to_struct_with_narrow_int_fields() {
+        #[derive(TryFromRow)]
+        struct Counters {
+            tiny: i8,
+            small: i16,
+        }
+        let result_set = ResultSet {
+            columns: vec![column("tiny"), column("small")],
+            rows: vec![Row {
+                values: vec![Value::tinyint(-128_i8), Value::smallint(32767_i16)],
+            }],
+            paging_state: None,
+        };
+
+        let mapper = result_set.mapper().unwrap();
+        for row in result_set.rows {
+            let counters: Counters = mapper.try_unpack(row).unwrap();
+            assert_eq!(counters.tiny, -128);
+            assert_eq!(counters.small, 32767);
+        }
+    }
+
+    #[test]
+    fn convert_row_to_struct_returns_err_on_narrow_int_out_of_range() {
+        #[derive(TryFromRow)]
+        #[allow(unused)]
+        struct Counters {
+            tiny: i8,
+            small: i16,
+        }
+        let result_set = ResultSet {
+            columns: vec![column("tiny"), column("small")],
+            rows: vec![
+                Row {
+                    values: vec![Value::bigint(128), Value::bigint(0)],
+                },
+                Row {
+                    values: vec![Value::bigint(0), Value::bigint(-32769)],
+                },
+            ],
+            paging_state: None,
+        };
+
+        let mapper = result_set.mapper().unwrap();
+        for row in result_set.rows {
+            let counters: Result<Counters, ConversionError> = mapper.try_unpack(row);
+            assert_eq!(
+                counters.err().unwrap().kind,
+                ConversionErrorKind::OutOfRange
+            );
+        }
+    }
+
+    #[test]
+    fn convert_strum_enum_as_text_round_trip() {
+        #[derive(Debug, PartialEq, strum::Display, strum::EnumString, IntoValue, TryFromValue)]
+        #[strum(serialize_all = "snake_case")]
+        #[stargate(as_text)]
+        enum Status {
+            Active,
+            OnHold,
+        }
+
+        let value = Value::from(Status::OnHold);
+        assert_eq!(value, Value::string("on_hold"));
+        let status: Status = value.try_into().unwrap();
+        assert_eq!(status, Status::OnHold);
+
+        let values: Value = vec![Status::Active, Status::OnHold].into();
+        assert_eq!(values, Value::list(vec!["active", "on_hold"]));
+
+        let result: Result<Status, ConversionError> = Value::string("closed").try_into();
+        assert_eq!(result.unwrap_err().kind, ConversionErrorKind::Incompatible);
+    }
+
+    #[test]
+    fn convert_bitflags_as_bits_round_trip() {
+        bitflags::bitflags! {
+            #[derive(Debug, PartialEq, IntoValue, TryFromValue)]
+            #[stargate(as_bits)]
+            struct Permissions: u32 {
+                const READ = 0b001;
+                const WRITE = 0b010;
+                const DELETE = 0b100;
+            }
+        }
+
+        let value = Value::from(Permissions::READ | Permissions::DELETE);
+        assert_eq!(value, Value::bigint(0b101));
+        let permissions: Permissions = value.try_into().unwrap();
+        assert_eq!(permissions, Permissions::READ | Permissions::DELETE);
+
+        let empty: Permissions = Value::bigint(0).try_into().unwrap();
+        assert_eq!(empty, Permissions::empty());
+
+        let result: Result<Permissions, ConversionError> = Value::bigint(0b1000).try_into();
+        assert_eq!(result.unwrap_err().kind, ConversionErrorKind::OutOfRange);
+    }
+
+    #[test]
+    fn convert_bitflags_in_collections_and_fields() {
+        bitflags::bitflags! {
+            #[derive(Clone, Copy, Debug, PartialEq, IntoValue, TryFromValue)]
+            #[stargate(as_bits)]
+            struct Permissions: u32 {
+                const READ = 0b01;
+                const WRITE = 0b10;
+            }
+        }
+        #[derive(Debug, PartialEq, IntoValue, TryFromValue)]
+        struct Grant {
+            user: String,
+            permissions: Permissions,
+            default_permissions: Option<Permissions>,
+        }
+
+        let list = Value::from(vec![Permissions::READ, Permissions::all()]);
+        assert_eq!(list, Value::list(vec![0b01, 0b11]));
+        let permissions: Vec<Permissions> = list.try_into().unwrap();
+        assert_eq!(permissions, vec![Permissions::READ, Permissions::all()]);
+
+        assert_eq!(Value::from(Some(Permissions::WRITE)), Value::bigint(0b10));
+        assert_eq!(Value::bigint(Permissions::WRITE), Value::bigint(0b10));
+
+        let grant = Grant {
+            user: "admin".to_string(),
+            permissions: Permissions::all(),
+            default_permissions: Some(Permissions::READ),
+        };
+        let value = Value::from(grant);
+        assert_eq!(
+            value,
+            Value::udt(vec![
+                ("user", Value::string("admin")),
+                ("permissions", Value::bigint(0b11)),
+                ("default_permissions", Value::bigint(0b01)),
+            ])
+        );
+        let grant: Grant = value.try_into().unwrap();
+        assert_eq!(grant.permissions, Permissions::all());
+        assert_eq!(grant.default_permissions, Some(Permissions::READ));
+    }
 }